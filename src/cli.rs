@@ -0,0 +1,285 @@
+use std::error::Error;
+use std::io::{ErrorKind, Write};
+
+use blute::flowers::breeding::plan_breeding;
+use blute::flowers::flower::{FlowerContext, FlowerType};
+use blute::genetics::{Distribution, Genotype};
+
+pub const USAGE: &str = "\
+usage:
+  blute [--seed <n>] [--load <path>] [--board <width>x<height>] [--tick-rate <ms>]
+                                           play the game
+  blute cross <species> <a> <b> [--colors] every child of two flowers and how likely it is
+  blute phenotype <species> <genotype>     the color of a flower
+  blute plan <species> <color>             the cheapest way to breed a color from shop seeds
+  blute seeds [species]                    the seeds sold in the shop
+
+Genotypes are written in allele notation like RRyyWWSs, or in binary shorthand like 2001.
+Results are printed one per line with tab separated fields:
+  cross        genotype, code, color, probability (color, probability with --colors)
+  plan         first parent, second parent, child, color, probability, flowers needed
+               (nothing if the shop sells seeds of the color)
+  seeds        species, genotype, code, color";
+
+/// A subcommand, given the arguments after its name and where to print to
+type Command = fn(&FlowerContext, &[String], &mut dyn Write) -> Result<(), Box<dyn Error>>;
+
+/// Runs the subcommand the arguments start with, or None if they don't start with one and the game should start
+pub fn run(args: &[String]) -> Option<Result<(), Box<dyn Error>>> {
+    let (command, rest) = args.split_first()?;
+    let run: Command = match command.as_str() {
+        "cross" => cross,
+        "phenotype" => phenotype,
+        "plan" => plan,
+        "seeds" => seeds,
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return Some(Ok(()));
+        }
+        _ => return None,
+    };
+    let mut out = std::io::stdout().lock();
+    let result =
+        FlowerContext::new().and_then(|flower_context| run(&flower_context, rest, &mut out));
+    // whatever the output was piped into stopped reading, like `head`
+    Some(
+        result.or_else(|e| match e.downcast_ref::<std::io::Error>() {
+            Some(io) if io.kind() == ErrorKind::BrokenPipe => Ok(()),
+            _ => Err(e),
+        }),
+    )
+}
+
+fn flower_type(flower_context: &FlowerContext, name: &str) -> Result<FlowerType, Box<dyn Error>> {
+    flower_context
+        .flower_type_by_name(name)
+        .ok_or_else(|| format!("no species called \"{}\", see `blute seeds`", name).into())
+}
+
+/// Reads a genotype in allele notation or binary shorthand
+fn genotype(
+    flower_context: &FlowerContext,
+    flower_type: FlowerType,
+    text: &str,
+) -> Result<Genotype, Box<dyn Error>> {
+    Ok(flower_context.parse_genotype(flower_type, text)?)
+}
+
+/// Most likely first
+fn sorted<T>(mut distribution: Distribution<T>) -> Distribution<T> {
+    distribution.sort_by(|a, b| b.1.total_cmp(&a.1));
+    distribution
+}
+
+fn cross(
+    flower_context: &FlowerContext,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    let by_color = args.iter().any(|a| a == "--colors");
+    let args: Vec<&String> = args.iter().filter(|a| *a != "--colors").collect();
+    let [species, a, b] = args[..] else {
+        return Err(format!("cross needs a species and two genotypes\n{}", USAGE).into());
+    };
+
+    let flower_type = flower_type(flower_context, species)?;
+    let a = genotype(flower_context, flower_type, a)?;
+    let b = genotype(flower_context, flower_type, b)?;
+    let children = a
+        .offspring_distribution(&b)
+        .ok_or("the genotypes can't be crossed")?;
+
+    if by_color {
+        let colors = flower_context
+            .phenotype_distribution(flower_type, &children)
+            .ok_or("some children have no color")?;
+        for (color, p) in sorted(colors) {
+            writeln!(out, "{}\t{}", color, p)?;
+        }
+    } else {
+        let letters = flower_context.gene_letters(flower_type);
+        for (child, p) in sorted(children) {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                child.to_allele_string(&letters),
+                child.to_code(),
+                flower_context
+                    .phenotype_name(flower_type, &child)
+                    .unwrap_or_default(),
+                p
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn phenotype(
+    flower_context: &FlowerContext,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    let [species, text] = args else {
+        return Err(format!("phenotype needs a species and a genotype\n{}", USAGE).into());
+    };
+    let flower_type = flower_type(flower_context, species)?;
+    let genotype = genotype(flower_context, flower_type, text)?;
+    let color = flower_context
+        .phenotype_name(flower_type, &genotype)
+        .ok_or("the genotype has no color")?;
+    writeln!(out, "{}", color)?;
+    Ok(())
+}
+
+fn plan(
+    flower_context: &FlowerContext,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    let [species, color] = args else {
+        return Err(format!("plan needs a species and a color\n{}", USAGE).into());
+    };
+    let flower_type = flower_type(flower_context, species)?;
+    let colors = flower_context.colors_of(flower_type);
+    let color = colors
+        .iter()
+        .find(|c| c.eq_ignore_ascii_case(color))
+        .ok_or_else(|| {
+            format!(
+                "{} comes in {}, not \"{}\"",
+                species,
+                colors.join(", "),
+                color
+            )
+        })?;
+    let plans = plan_breeding(flower_context, flower_type, color);
+    let plan = plans.first().ok_or_else(|| {
+        format!(
+            "{} {} can't be bred from shop seeds telling flowers apart by color",
+            color, species
+        )
+    })?;
+
+    let letters = flower_context.gene_letters(flower_type);
+    for step in plan.steps() {
+        let (a, b) = step.parents();
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            a.to_allele_string(&letters),
+            b.to_allele_string(&letters),
+            step.child().to_allele_string(&letters),
+            step.color(),
+            step.probability(),
+            step.flowers_needed()
+        )?;
+    }
+    Ok(())
+}
+
+fn seeds(
+    flower_context: &FlowerContext,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    let flower_types = match args {
+        [] => flower_context.flower_types(),
+        [species] => vec![flower_type(flower_context, species)?],
+        _ => return Err(format!("seeds takes at most one species\n{}", USAGE).into()),
+    };
+    for flower_type in flower_types {
+        let letters = flower_context.gene_letters(flower_type);
+        for seed in flower_context.seeds_of(flower_type) {
+            let genotype = seed.genotype();
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                seed.info().name(),
+                genotype.to_allele_string(&letters),
+                genotype.to_code(),
+                flower_context
+                    .phenotype_name(flower_type, &genotype)
+                    .unwrap_or_default()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What the command printed, or its error
+    fn output(command: Command, args: &[&str]) -> Result<String, String> {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let mut out: Vec<u8> = vec![];
+        command(&flower_context, &args, &mut out).map_err(|e| e.to_string())?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn cross_lists_every_child() {
+        assert_eq!(
+            output(cross, &["rose", "RRyyWWSs", "0010"]).unwrap(),
+            "RryyWwSs\t1011\tPink\t0.25\n\
+             RryyWwss\t1010\tRed\t0.25\n\
+             RryyWWSs\t1001\tPink\t0.25\n\
+             RryyWWss\t1000\tRed\t0.25\n"
+        );
+        assert_eq!(
+            output(cross, &["rose", "--colors", "2001", "0010"]).unwrap(),
+            "Pink\t0.5\nRed\t0.5\n"
+        );
+        let e = output(cross, &["rose", "2001"]).unwrap_err();
+        assert!(e.starts_with("cross needs a species and two genotypes\nusage:"));
+        let e = output(cross, &["rose", "2001", "9"]).unwrap_err();
+        assert!(e.contains("can't read \"9\" as a genotype"), "{}", e);
+    }
+
+    #[test]
+    fn phenotype_prints_the_color() {
+        assert_eq!(output(phenotype, &["rose", "RRyyWWss"]).unwrap(), "Black\n");
+        assert_eq!(output(phenotype, &["Rose", "2001"]).unwrap(), "Red\n");
+        let e = output(phenotype, &["fern", "2001"]).unwrap_err();
+        assert_eq!(e, "no species called \"fern\", see `blute seeds`");
+        assert!(output(phenotype, &["rose"]).is_err());
+    }
+
+    #[test]
+    fn plan_ends_with_the_color() {
+        let blue = output(plan, &["rose", "BLUE"]).unwrap();
+        let steps: Vec<Vec<&str>> = blue.lines().map(|l| l.split('\t').collect()).collect();
+        assert!(steps.iter().all(|step| step.len() == 6));
+        assert_eq!(steps.last().unwrap()[2..4], ["RRYYwwss", "Blue"]);
+        // the shop already sells red roses
+        assert_eq!(output(plan, &["rose", "red"]).unwrap(), "");
+
+        let e = output(plan, &["rose", "green"]).unwrap_err();
+        assert_eq!(
+            e,
+            "rose comes in White, Purple, Yellow, Red, Pink, Orange, Black, Blue, not \"green\""
+        );
+        assert!(
+            output(plan, &["rose"])
+                .unwrap_err()
+                .starts_with("plan needs")
+        );
+    }
+
+    #[test]
+    fn seeds_lists_the_shop() {
+        assert_eq!(
+            output(seeds, &["rose"]).unwrap(),
+            "Rose (acnh)\tRRyyWWSs\t2001\tRed\n\
+             Rose (acnh)\trryyWwss\t0010\tWhite\n\
+             Rose (acnh)\trrYYWWss\t0200\tYellow\n"
+        );
+        let all = output(seeds, &[]).unwrap();
+        assert!(all.lines().any(|l| l.starts_with("Windflower (acnh)\t")));
+        assert!(all.lines().all(|l| l.split('\t').count() == 4));
+        let e = output(seeds, &["rose", "tulip"]).unwrap_err();
+        assert!(e.starts_with("seeds takes at most one species\n"));
+    }
+}
@@ -1,98 +1,924 @@
-use std::io::{StdoutLock, Write};
-use std::sync::MutexGuard;
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::thread::JoinHandle;
-use std::vec::IntoIter;
+use std::time::Duration;
 
 use getch_rs::Key;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 
 use crate::flowers::flower::FlowerContext;
-use crate::ui::shop::Shop;
-use crate::ui::terminal::{self, KeyStack, KeyStackInner, new_keystack};
-use crate::ui::{Grid, board::Board, display::Display, shop, to_grid};
+use crate::flowers::lineage::Pedigree;
+use crate::save::{DEFAULT_SAVE_PATH, SAVE_VERSION, SaveData};
+use crate::ui::backend::{Backend, MemoryBackend, TerminalBackend};
+use crate::ui::board::BreedEvent;
+use crate::ui::board::DEFAULT_BOARD_SIZE;
+use crate::ui::family_tree::FamilyTree;
+use crate::ui::inspector::Inspector;
+use crate::ui::inventory::Inventory;
+use crate::ui::layout::{Layout, Rect};
+use crate::ui::menu::{Menu, MenuItem, MenuPage, Pane};
+use crate::ui::shop::{STARTING_BELLS, Shop};
+use crate::ui::stats::GardenStats;
+use crate::ui::terminal::{self, Event, EventStream};
+use crate::ui::{board::Board, display::Display, text_row};
 
-pub struct Context {
+/// The whole game. Draws to the terminal unless it's given another backend, like a `MemoryBackend` for tests
+pub struct Context<B: Backend = TerminalBackend> {
     flower_context: FlowerContext,
     display: Display,
     board: Board,
     shop: Shop,
+    inspector: Inspector,
+    inventory: Inventory,
+    menu: Menu,
+    /// Shown over the board while open
+    family_tree: Option<FamilyTree>,
+    /// The pane that movement and action keys go to
+    focus: Pane,
+    /// Set when Quit is picked in the menu
+    quit: bool,
 
-    keys: KeyStack,
-    _guard: JoinHandle<()>,
+    /// Every random thing in the game comes from this, so a session can be replayed from its seed
+    seed: u64,
+    rng: StdRng,
 
-    stdout_lock: StdoutLock<'static>,
+    day: u32,
+    bells: u32,
+    /// Whether the watering can waters the cells around the cursor too
+    area_watering: bool,
+    /// Whether the stats are shown when there's room for them
+    show_stats: bool,
+    /// The columns and rows the game has to draw in
+    screen_size: (usize, usize),
+    /// Whether days pass on their own, one every `DAY_LENGTH`
+    auto_day: bool,
+    /// Ticks since the last day passed on its own
+    ticks: u32,
+    tick_rate: Duration,
+    /// What bred on the last day
+    events: Vec<BreedEvent>,
+    pedigree: Pedigree,
+
+    /// Where the game is saved to and loaded from
+    save_path: PathBuf,
+    /// A message for the player shown under the display, like a save failing
+    status: String,
+    /// Set when the layout changes, since the old frame could be bigger than the new one
+    needs_clear: bool,
+
+    event_stream: EventStream,
+    /// The threads sending into the event stream, none when events are fed in by hand
+    _guards: Vec<JoinHandle<()>>,
+
+    backend: B,
 }
 
+/// How often time-based things happen unless it's set with `--tick-rate`
+pub const DEFAULT_TICK_RATE: Duration = Duration::from_millis(250);
+/// The screen size when the terminal's can't be found, and for headless games
+pub const DEFAULT_SCREEN_SIZE: (usize, usize) = (80, 24);
+/// How long a day lasts when days pass on their own
+pub const DAY_LENGTH: Duration = Duration::from_secs(10);
+
 impl Context {
-    pub fn new() -> Context {
-        let display = Display::new();
-        let (keys, _guard) = new_keystack();
-        let board = Board::new(display.board_size()).expect("Could not create board");
-        let flower_context = FlowerContext::new().expect("Could not create flower context.");
+    pub fn new() -> Result<Context, Box<dyn Error>> {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Result<Context, Box<dyn Error>> {
+        Self::with_tick_rate(seed, DEFAULT_TICK_RATE)
+    }
+
+    /// Errors if the custom species can't be loaded, before anything is started
+    pub fn with_tick_rate(seed: u64, tick_rate: Duration) -> Result<Context, Box<dyn Error>> {
+        let flower_context = FlowerContext::new()?;
+        let event_stream = EventStream::new();
+        #[cfg_attr(not(unix), allow(unused_mut))]
+        let mut guards = vec![
+            event_stream.spawn_keyboard(),
+            event_stream.spawn_ticker(tick_rate),
+        ];
+        // the game still works without it, the screen just doesn't follow resizes
+        #[cfg(unix)]
+        match event_stream.spawn_resize_watcher() {
+            Ok(guard) => guards.push(guard),
+            Err(e) => event_stream.send(Event::Error(format!(
+                "Couldn't watch for terminal resizes: {}",
+                e
+            ))),
+        }
+        let screen_size = terminal::terminal_size().unwrap_or(DEFAULT_SCREEN_SIZE);
+        Ok(Self::with_backend(
+            seed,
+            flower_context,
+            TerminalBackend::new(),
+            event_stream,
+            guards,
+            tick_rate,
+            screen_size,
+        ))
+    }
+}
+
+impl Context<MemoryBackend> {
+    /// A game without a terminal or keyboard, driven by `press_keys` and `push_events` and drawn to memory.
+    /// Custom species come from `species_dir` if there is one, so the game doesn't depend on the machine
+    pub fn headless(
+        seed: u64,
+        species_dir: Option<&Path>,
+    ) -> Result<Context<MemoryBackend>, Box<dyn Error>> {
+        let flower_context = match species_dir {
+            Some(dir) => FlowerContext::with_species_dir(dir)?,
+            None => FlowerContext::acnh_only()?,
+        };
+        Ok(Self::with_backend(
+            seed,
+            flower_context,
+            MemoryBackend::new(),
+            EventStream::new(),
+            vec![],
+            DEFAULT_TICK_RATE,
+            DEFAULT_SCREEN_SIZE,
+        ))
+    }
+}
+
+impl<B: Backend> Context<B> {
+    pub fn with_backend(
+        seed: u64,
+        flower_context: FlowerContext,
+        backend: B,
+        event_stream: EventStream,
+        _guards: Vec<JoinHandle<()>>,
+        tick_rate: Duration,
+        screen_size: (usize, usize),
+    ) -> Context<B> {
+        let board = Board::new(DEFAULT_BOARD_SIZE).expect("Could not create board");
+        // replaced by relayout before anything is drawn
+        let display = Display::too_small(screen_size, screen_size, screen_size);
         let shop = Shop::new(&flower_context);
-        Context {
+        let inspector = Inspector::new((16, 7));
+        let mut context = Context {
             flower_context,
             display,
             board,
             shop,
-            keys,
-            _guard,
-            stdout_lock: std::io::stdout().lock(),
-        }
+            inspector,
+            inventory: Inventory::new(),
+            menu: Menu::new(),
+            family_tree: None,
+            focus: Pane::Board,
+            quit: false,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            day: 0,
+            bells: STARTING_BELLS,
+            area_watering: false,
+            show_stats: true,
+            screen_size,
+            auto_day: false,
+            ticks: 0,
+            tick_rate,
+            events: vec![],
+            pedigree: Pedigree::new(),
+            save_path: PathBuf::from(DEFAULT_SAVE_PATH),
+            status: String::new(),
+            needs_clear: true,
+            event_stream,
+            _guards,
+            backend,
+        };
+        context.relayout();
+        context
     }
 
     pub fn to_string(&self) -> String {
         self.display.to_string()
     }
 
-    pub fn refresh(&mut self) {
-        self.display.refresh(&mut self.stdout_lock);
+    /// Sends the frame to the backend, wiping the old one first if the layout changed
+    pub fn refresh(&mut self) -> io::Result<()> {
+        if self.needs_clear {
+            self.backend.clear()?;
+            self.needs_clear = false;
+        }
+        self.display.refresh(&mut self.backend, &self.status)
     }
 
-    pub fn get_keystack(&self) -> MutexGuard<'_, KeyStackInner> {
-        self.keys.lock().expect("Couldn't get keys.")
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Handles each event in turn as if it just happened, drawing a frame after each.
+    /// Returns `true` if one of them quit the game
+    pub fn push_events(&mut self, events: impl IntoIterator<Item = Event>) -> io::Result<bool> {
+        for event in events {
+            self.event_stream.send(event);
+            if self.update()? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Presses each key in turn as if it was typed, like `push_events`
+    pub fn press_keys(&mut self, keys: impl IntoIterator<Item = Key>) -> io::Result<bool> {
+        self.push_events(keys.into_iter().map(Event::from_key))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn events(&self) -> Vec<BreedEvent> {
+        self.events.clone()
+    }
+
+    /// Moves on to the next day, letting the flowers on the board breed
+    pub fn next_day(&mut self) {
+        self.day += 1;
+        self.events = self.board.tick(&mut self.rng, &mut self.pedigree, self.day);
     }
 
     pub fn board(&mut self) -> &mut Board {
         &mut self.board
     }
 
-    fn handle_keys(&mut self, keys: IntoIter<Key>) {
-        for key in keys {
-            match key {
-                // board cursor movement
-                Key::Char('w') => self.board.move_cursor((0, 1)),
-                Key::Char('a') => self.board.move_cursor((-1, 0)),
-                Key::Char('s') => self.board.move_cursor((0, -1)),
-                Key::Char('d') => self.board.move_cursor((1, 0)),
-                // shop cursor movement
-                Key::Up => self.shop.move_cursor((0, 1)),
-                Key::Left => self.shop.move_cursor((-1, 0)),
-                Key::Down => self.shop.move_cursor((0, -1)),
-                Key::Right => self.shop.move_cursor((1, 0)),
-                // flowers
-                Key::Char(' ') => self.board.set_flower_at_cursor(self.shop.selected_flower()),
-                Key::Char('c') => self.board.set_flower_at_cursor(None),
-                _ => {}
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        SaveData {
+            version: SAVE_VERSION,
+            seed: self.seed,
+            day: self.day,
+            board: self.board.to_saved(),
+            shop_cursor: self.shop.cursor_pos(),
+            inventory: self.inventory.to_saved(),
+            bells: self.bells,
+            area_watering: self.area_watering,
+            pedigree: self.pedigree.clone(),
+        }
+        .write(path)
+    }
+
+    /// Replaces the game with the one saved at `path`, which also becomes where the game is saved to
+    pub fn load(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let data = SaveData::read(path)?;
+        // everything that can fail comes first, so a bad save leaves the game as it was
+        let board = Board::from_saved(data.board, &self.flower_context)
+            .map_err(|e| format!("Save file has an invalid board: {}", e))?;
+        let inventory = Inventory::from_saved(data.inventory, &self.flower_context)
+            .map_err(|e| format!("Save file has an invalid inventory: {}", e))?;
+
+        self.board = board;
+        self.inventory = inventory;
+        self.shop.set_cursor(data.shop_cursor);
+        self.shop.move_cursor((0, 0));
+        self.seed = data.seed;
+        self.day = data.day;
+        self.bells = data.bells;
+        // the rng state isn't saved, so carry on from a stream picked by the seed and the day
+        self.rng = StdRng::seed_from_u64(data.seed.wrapping_add(data.day as u64));
+        self.area_watering = data.area_watering;
+        self.pedigree = data.pedigree;
+        self.family_tree = None;
+        self.events = vec![];
+        self.save_path = path.to_path_buf();
+        // the saved board could be another size
+        self.relayout();
+        Ok(())
+    }
+
+    /// The names and values of the settings page, in order
+    fn settings(&self) -> Vec<(String, String)> {
+        let on_off = |b: bool| if b { "on" } else { "off" }.to_string();
+        vec![
+            ("area water".to_string(), on_off(self.area_watering)),
+            ("stats".to_string(), on_off(self.show_stats)),
+            ("auto day".to_string(), on_off(self.auto_day)),
+            ("seed".to_string(), self.seed.to_string()),
+        ]
+    }
+
+    fn toggle_setting(&mut self, index: usize) {
+        match index {
+            0 => self.area_watering = !self.area_watering,
+            1 => {
+                self.show_stats = !self.show_stats;
+                self.relayout();
+            }
+            2 => {
+                self.auto_day = !self.auto_day;
+                self.ticks = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Swaps the board for an empty one of a size. None if it's 0 either way
+    pub fn set_board_size(&mut self, size: (usize, usize)) -> Option<()> {
+        self.board = Board::new(size)?;
+        self.relayout();
+        Some(())
+    }
+
+    /// Starts over with an empty board and a new seed
+    pub fn new_game(&mut self) {
+        self.seed = rand::random();
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.board = Board::new(self.board.size()).expect("Could not create board");
+        self.relayout();
+        self.inventory = Inventory::new();
+        self.bells = STARTING_BELLS;
+        self.pedigree = Pedigree::new();
+        self.family_tree = None;
+        self.day = 0;
+        self.events = vec![];
+        self.status = format!("New game with seed {}", self.seed);
+    }
+
+    fn save_game(&mut self) {
+        self.status = match self.save(&self.save_path) {
+            Ok(()) => format!("Saved to {}", self.save_path.display()),
+            Err(e) => format!("Could not save: {}", e),
+        }
+    }
+
+    fn load_game(&mut self) {
+        let path = self.save_path.clone();
+        self.status = match self.load(&path) {
+            Ok(()) => format!("Loaded {}", path.display()),
+            Err(e) => format!("Could not load: {}", e),
+        }
+    }
+
+    /// Which way a movement key points, for both wasd and the arrow keys
+    fn direction(key: &Key) -> Option<(isize, isize)> {
+        match key {
+            Key::Char('w') | Key::Up => Some((0, 1)),
+            Key::Char('a') | Key::Left => Some((-1, 0)),
+            Key::Char('s') | Key::Down => Some((0, -1)),
+            Key::Char('d') | Key::Right => Some((1, 0)),
+            _ => None,
+        }
+    }
+
+    fn handle_board_key(&mut self, key: Key) {
+        if let Some(d_pos) = Self::direction(&key) {
+            self.board.move_cursor(d_pos);
+            return;
+        }
+        match key {
+            // big boards
+            Key::PageUp | Key::Char('W') => self.board.move_cursor_by_page((0, 1)),
+            Key::PageDown | Key::Char('S') => self.board.move_cursor_by_page((0, -1)),
+            Key::Char('A') => self.board.move_cursor_by_page((-1, 0)),
+            Key::Char('D') => self.board.move_cursor_by_page((1, 0)),
+            Key::Home => self.board.jump_to_row_end(false),
+            Key::End => self.board.jump_to_row_end(true),
+            // flowers
+            Key::Char(' ') => self.plant_from_inventory(),
+            Key::Char('g') => {
+                if let Some((flower, lineage)) = self.board.take_flower(self.board.cursor_pos()) {
+                    self.inventory.add_picked(flower, lineage);
+                }
+            }
+            Key::Char('c') => self.board.set_flower_at_cursor(None),
+            Key::Char('f') => self.board.pick_up_or_put_down(),
+            Key::Char('t') => match self.board.lineage(self.board.cursor_pos()) {
+                Some(lineage) => self.family_tree = Some(FamilyTree::new(lineage)),
+                None => self.status = "No record of where this flower came from".to_string(),
+            },
+            // watering
+            Key::Char('e') => self.board.water_at_cursor(self.area_watering),
+            Key::Char('q') => self.area_watering = !self.area_watering,
+            _ => {}
+        }
+    }
+
+    fn handle_shop_key(&mut self, key: Key) {
+        if let Some(d_pos) = Self::direction(&key) {
+            self.shop.move_cursor(d_pos);
+            return;
+        }
+        match key {
+            Key::Char(' ') => self.buy_seed(),
+            // done shopping, go plant
+            Key::Char('\r') => self.focus = Pane::Board,
+            _ => {}
+        }
+    }
+
+    fn handle_inventory_key(&mut self, key: Key) {
+        if let Some((dx, _)) = Self::direction(&key) {
+            self.inventory.move_cursor(dx);
+            return;
+        }
+        match key {
+            Key::Char(' ') => self.plant_from_inventory(),
+            Key::Char('x') | Key::Backspace | Key::Delete => self.inventory.discard_selected(),
+            Key::Char('$') => self.sell_selected(),
+            Key::Char('\r') => self.focus = Pane::Board,
+            _ => {}
+        }
+    }
+
+    /// Buys the selected shop seed into the inventory
+    fn buy_seed(&mut self) {
+        let Some(seed) = self.shop.selected_flower() else {
+            return;
+        };
+        let price = self.flower_context.seed_price(seed.info().flower_type());
+        match self.bells.checked_sub(price) {
+            Some(bells) => {
+                self.bells = bells;
+                self.inventory.add_seeds(seed, 1);
             }
+            None => self.status = format!("Not enough bells, seeds cost {}", price),
+        }
+    }
+
+    /// Sells one seed or the picked flower in the selected inventory slot
+    fn sell_selected(&mut self) {
+        let Some(price) = self
+            .inventory
+            .selected()
+            .map(|item| item.sell_price(&self.flower_context))
+        else {
+            return;
+        };
+        self.inventory.take_selected();
+        self.bells += price;
+        self.status = format!("Sold for {} bells", price);
+    }
+
+    /// Plants the selected inventory item on the board cursor, if the cell is free
+    fn plant_from_inventory(&mut self) {
+        let pos = self.board.cursor_pos();
+        if !matches!(self.board.get_flower(pos), Some(None)) {
+            return;
+        }
+        if let Some((flower, lineage)) = self.inventory.take_selected() {
+            let lineage = lineage.unwrap_or_else(|| self.pedigree.record_seed(&flower, self.day));
+            self.board.plant(pos, Some(flower), Some(lineage));
         }
     }
 
-    /// Game update. Returns `true` if the loop should quit.
-    pub fn update(&mut self) -> bool {
-        let mut key_stack_mutex = self.get_keystack();
-        if key_stack_mutex.should_quit() {
-            true
+    /// Anything but y is a no
+    fn handle_confirm_key(&mut self, key: Key) {
+        let Some(item) = self.menu.answer() else {
+            return;
+        };
+        if key == Key::Char('y') {
+            self.pick_menu_item(item);
         } else {
-            let keys = key_stack_mutex.poll_keys();
-            if keys.len() != 0 {
-                drop(key_stack_mutex);
-                self.handle_keys(keys);
-                self.display
-                    .stamp(self.board.to_grid(&self.flower_context), (1, 1));
-                self.display.stamp(self.shop.to_grid(&self.flower_context), (22, 3));
-                self.refresh();
+            self.status = format!("{} cancelled", item.label());
+        }
+    }
+
+    fn handle_menu_key(&mut self, key: Key) {
+        if let Some((_, dy)) = Self::direction(&key) {
+            self.menu.move_cursor(-dy, 0);
+            return;
+        }
+        if !matches!(key, Key::Char(' ') | Key::Char('\r')) {
+            return;
+        }
+        let item = self.menu.selected();
+        if item.needs_confirming() {
+            self.ask(item);
+        } else {
+            self.pick_menu_item(item);
+        }
+    }
+
+    fn ask(&mut self, item: MenuItem) {
+        self.menu.ask(item);
+        self.status = format!("{}? Anything not saved is lost. y / n", item.label());
+    }
+
+    fn pick_menu_item(&mut self, item: MenuItem) {
+        match item {
+            MenuItem::NewGame => self.new_game(),
+            MenuItem::Save => self.save_game(),
+            MenuItem::Load => self.load_game(),
+            MenuItem::Settings => self.menu.open_page(MenuPage::Settings),
+            MenuItem::Help => self.menu.open_page(MenuPage::Help),
+            MenuItem::Encyclopedia => self.menu.open_page(MenuPage::Encyclopedia),
+            MenuItem::Quit => self.quit = true,
+        }
+    }
+
+    /// Keys go to the open page until it's closed
+    fn handle_page_key(&mut self, page: MenuPage, key: Key) {
+        let page_length = match page {
+            MenuPage::Settings => self.settings().len(),
+            MenuPage::Help => Menu::help_lines().len(),
+            MenuPage::Encyclopedia => self.flower_context.flower_types().len(),
+        };
+        if let Some((dx, dy)) = Self::direction(&key) {
+            self.menu.move_cursor(dx - dy, page_length);
+            return;
+        }
+        match (page, key) {
+            (MenuPage::Settings, Key::Char(' ')) => self.toggle_setting(self.menu.page_cursor()),
+            (
+                _,
+                Key::Char(' ') | Key::Char('\r') | Key::Backspace | Key::Delete | Key::Char('\t'),
+            ) => self.menu.close_page(),
+            _ => {}
+        }
+    }
+
+    /// Keys scroll the family tree until it's closed
+    fn handle_family_tree_key(&mut self, key: Key) {
+        if let Some((_, dy)) = Self::direction(&key) {
+            if let Some(tree) = self.family_tree.as_mut() {
+                tree.scroll(-dy);
+            }
+            return;
+        }
+        if let Key::Char('t' | ' ' | '\r' | '\t') | Key::Backspace | Key::Delete = key {
+            self.family_tree = None;
+        }
+    }
+
+    fn handle_key(&mut self, key: Key) {
+        if self.family_tree.is_some() {
+            self.handle_family_tree_key(key);
+            return;
+        }
+        if self.menu.confirming().is_some() {
+            self.handle_confirm_key(key);
+            return;
+        }
+        if let Some(page) = self.menu.page() {
+            self.handle_page_key(page, key);
+            return;
+        }
+        match key {
+            // focus
+            Key::Char('\t') => self.focus = self.focus.next(),
+            Key::BackTab => self.focus = self.focus.previous(),
+            // time
+            Key::Char('n') => self.next_day(),
+            // saving
+            Key::Char('p') => self.save_game(),
+            Key::Char('o') => self.ask(MenuItem::Load),
+            _ => match self.focus {
+                Pane::Board => self.handle_board_key(key),
+                Pane::Shop => self.handle_shop_key(key),
+                Pane::Inventory => self.handle_inventory_key(key),
+                Pane::Menu => self.handle_menu_key(key),
+            },
+        }
+    }
+
+    /// Lays the panes out for the screen size again, like after a resize
+    fn relayout(&mut self) {
+        // the last row is for the status
+        let available = (self.screen_size.0, self.screen_size.1.saturating_sub(1));
+        let board_pane = Layout::board_pane(self.board.size());
+        self.display = match Layout::fit(available, board_pane, self.show_stats) {
+            Some(layout) => Display::new(layout),
+            None => {
+                let (width, height) = Layout::min_size();
+                Display::too_small(available, self.screen_size, (width, height + 1))
+            }
+        };
+        self.needs_clear = true;
+        // so the board knows how much of it is on screen before the next key
+        self.draw();
+    }
+
+    /// Draws every pane onto the display
+    fn draw(&mut self) {
+        self.display.reset_display();
+        let Some(layout) = self.display.layout().cloned() else {
+            return;
+        };
+
+        let focused = Menu::focused_blueprint();
+        let title = |rect: Rect, text: &str, focus: bool| {
+            let padding = rect.width.saturating_sub(text.len()) / 2;
+            text_row(
+                &format!("{}{}", " ".repeat(padding), text),
+                rect.width,
+                focus.then_some(&focused),
+            )
+        };
+        self.display.stamp(
+            vec![title(
+                layout.shop_title(),
+                "SHOP!",
+                self.focus == Pane::Shop,
+            )],
+            layout.shop_title().pos(),
+        );
+        self.display.stamp(
+            vec![title(
+                layout.menu_title(),
+                "MENU!",
+                self.focus == Pane::Menu,
+            )],
+            layout.menu_title().pos(),
+        );
+
+        let board = layout.board();
+        match self
+            .menu
+            .page_grid(&self.flower_context, &self.settings(), board.size())
+        {
+            Some(page) => self.display.stamp(page, board.pos()),
+            None => self.display.stamp(
+                self.board.to_grid(&self.flower_context, board.size()),
+                board.pos(),
+            ),
+        }
+        if let Some(map) = layout.map() {
+            self.display
+                .stamp(self.board.minimap(map.size()), map.pos());
+        }
+        if self.board.is_scrolled() {
+            // shown on the bottom right edge of the board, so it's clear where the view is
+            let (x, y) = self.board.cursor_pos();
+            let text = format!(" {},{} ", x + 1, y + 1);
+            let width = text.chars().count();
+            self.display.stamp(
+                vec![text_row(&text, width, None)],
+                (board.right().saturating_sub(width + 1), board.bottom()),
+            );
+        }
+        // shown on the top edge of the board
+        self.display.stamp(
+            vec![text_row(
+                &format!(" {} bells ", self.bells),
+                board.width.min(17),
+                None,
+            )],
+            (board.x + 1, board.y - 1),
+        );
+        if let Some(tree) = self.family_tree.as_mut() {
+            // drawn over everything but the bag
+            let size = (layout.size().0 - 2, board.height);
+            self.display.stamp(
+                tree.to_grid(&self.pedigree, &self.flower_context, size),
+                board.pos(),
+            );
+        }
+        if let Some(held) = self.board.held() {
+            // shown on the bottom edge of the board
+            let mut row = text_row(" holding ", 9, None);
+            row.push(held.to_string(&self.flower_context));
+            row.push(" ".to_string());
+            self.display.stamp(vec![row], (board.x + 1, board.bottom()));
+        }
+
+        let shop = layout.shop();
+        self.display.stamp(
+            self.shop.to_grid(&self.flower_context),
+            (shop.x + 1, shop.y),
+        );
+        let bag_focused = self.focus == Pane::Inventory;
+        self.display.stamp(
+            vec![title(layout.bag_title(), "BAG!", bag_focused)],
+            layout.bag_title().pos(),
+        );
+        let bag = layout.bag();
+        self.display.stamp(
+            self.inventory.to_grid(&self.flower_context, bag.width),
+            bag.pos(),
+        );
+        let menu = layout.menu();
+        self.display.stamp(
+            self.menu.to_grid(self.focus == Pane::Menu, menu.size()),
+            menu.pos(),
+        );
+        let inspector = layout.inspector();
+        self.display.stamp(
+            self.inspector.to_grid(
+                self.board.flower_at_cursor(),
+                self.board.is_watered(self.board.cursor_pos()),
+                &self.flower_context,
+            ),
+            (inspector.x + 1, inspector.y),
+        );
+        if let Some(stats) = layout.stats() {
+            self.display.stamp(
+                GardenStats::new(
+                    &self.board,
+                    self.day,
+                    self.events.len(),
+                    &self.flower_context,
+                )
+                .to_grid(&self.flower_context, (stats.width - 2, stats.height)),
+                (stats.x + 1, stats.y),
+            );
+        }
+    }
+
+    /// Handles an event. Returns `true` if the frame needs drawing again
+    fn handle_event(&mut self, event: Event) -> bool {
+        match event {
+            Event::Key(key) => self.handle_key(key),
+            Event::Resize(size) => {
+                self.screen_size = size;
+                self.relayout();
+            }
+            Event::Tick => {
+                if !self.auto_day {
+                    return false;
+                }
+                self.ticks += 1;
+                let ticks_per_day =
+                    (DAY_LENGTH.as_millis() / self.tick_rate.as_millis().max(1)).max(1);
+                if (self.ticks as u128) < ticks_per_day {
+                    return false;
+                }
+                self.ticks = 0;
+                self.next_day();
             }
-            false
+            Event::Quit => self.quit = true,
+            Event::Error(message) => self.status = message,
+        }
+        true
+    }
+
+    /// Game update. Waits for something to happen, handles it and anything else that happened meanwhile,
+    /// then draws the frame. Returns `true` if the loop should quit.
+    /// Errors if the frame couldn't be drawn
+    pub fn update(&mut self) -> io::Result<bool> {
+        let mut redraw = self.handle_event(self.event_stream.next());
+        while let Some(event) = self.event_stream.try_next() {
+            redraw |= self.handle_event(event);
+        }
+        if self.quit {
+            return Ok(true);
+        }
+        if redraw {
+            self.draw();
+            self.refresh()?;
+        }
+        Ok(false)
+    }
+
+    /// Draws the first frame and runs the game until it quits or can't draw anymore
+    pub fn run(&mut self) -> io::Result<()> {
+        self.draw();
+        self.refresh()?;
+        while !self.update()? {}
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::backend::Frame;
+
+    fn headless() -> Context<MemoryBackend> {
+        let mut context = Context::headless(1, None).unwrap();
+        context.refresh().unwrap();
+        context
+    }
+
+    fn last_frame(context: &Context<MemoryBackend>) -> Frame {
+        context.backend().last_frame().unwrap().clone()
+    }
+
+    fn shows(frame: &Frame, text: &str) -> bool {
+        frame.lines().iter().any(|line| line.contains(text))
+    }
+
+    #[test]
+    fn the_first_frame_has_every_pane() {
+        let frame = last_frame(&headless());
+        for title in ["SHOP!", "MENU!", "BAG!", "1000 bells"] {
+            assert!(
+                shows(&frame, title),
+                "no {} in\n{}",
+                title,
+                frame.to_snapshot()
+            );
         }
+        assert!(shows(&frame, "New game"));
+    }
+
+    #[test]
+    fn a_watered_seedling_matches_the_golden_frame() {
+        let mut context = headless();
+        // buy red rose seeds, plant one where the cursor starts and water it
+        context
+            .press_keys([
+                Key::Char('\t'),
+                Key::Char(' '),
+                Key::Char('\r'),
+                Key::Char(' '),
+                Key::Char('e'),
+            ])
+            .unwrap();
+        assert_eq!(
+            last_frame(&context).to_snapshot(),
+            include_str!("snapshots/watered_seedling.txt")
+        );
+    }
+
+    #[test]
+    fn the_menu_scrolls_to_the_selected_item() {
+        let mut context = headless();
+        // board, shop, bag, menu
+        context.press_keys(vec![Key::Char('\t'); 3]).unwrap();
+        context.press_keys(vec![Key::Char('s'); 2]).unwrap();
+        let frame = last_frame(&context);
+        assert!(shows(&frame, "| Load"), "{}", frame.to_snapshot());
+        assert!(!shows(&frame, "New game"));
+    }
+
+    #[test]
+    fn loading_waits_for_a_yes() {
+        let mut context = headless();
+        context.press_keys([Key::Char('o')]).unwrap();
+        let frame = last_frame(&context);
+        assert!(frame.status().contains("Load?"), "{}", frame.status());
+        assert!(shows(&frame, "Load?"));
+
+        context.press_keys([Key::Char('n')]).unwrap();
+        let frame = last_frame(&context);
+        assert_eq!(frame.status(), "Load cancelled");
+        assert!(shows(&frame, "New game"));
+    }
+
+    #[test]
+    fn a_new_game_from_the_menu_starts_over_after_a_yes() {
+        let mut context = headless();
+        // buy a bag of seeds
+        context
+            .press_keys([Key::Char('\t'), Key::Char(' ')])
+            .unwrap();
+        assert!(
+            shows(&last_frame(&context), "760 bells"),
+            "{}",
+            last_frame(&context).to_snapshot()
+        );
+
+        context
+            .press_keys([Key::Char('\t'), Key::Char('\t'), Key::Char(' ')])
+            .unwrap();
+        assert!(shows(&last_frame(&context), "760 bells"));
+        context.press_keys([Key::Char('y')]).unwrap();
+        let frame = last_frame(&context);
+        assert!(shows(&frame, "1000 bells"));
+        assert!(frame.status().starts_with("New game"), "{}", frame.status());
+    }
+
+    #[test]
+    fn a_tiny_terminal_says_what_size_is_needed() {
+        let mut context = headless();
+        context.push_events([Event::Resize((40, 10))]).unwrap();
+        let frame = last_frame(&context);
+        assert!(shows(&frame, "terminal too small"));
+        assert!(shows(&frame, "need 50x15"), "{}", frame.to_snapshot());
+        assert!(shows(&frame, "have 40x10"));
+
+        context.push_events([Event::Resize((80, 24))]).unwrap();
+        assert!(shows(&last_frame(&context), "SHOP!"));
+    }
+
+    #[test]
+    fn errors_go_to_the_status_line() {
+        let mut context = headless();
+        let error = Event::Error("Couldn't read the keyboard: gone".to_string());
+        assert!(!context.push_events([error]).unwrap());
+        assert_eq!(
+            last_frame(&context).status(),
+            "Couldn't read the keyboard: gone"
+        );
+    }
+
+    #[test]
+    fn escape_quits() {
+        let mut context = headless();
+        let frames = context.backend().frames().len();
+        assert!(context.press_keys([Key::Esc]).unwrap());
+        assert_eq!(context.backend().frames().len(), frames);
+    }
+
+    #[test]
+    fn headless_games_load_species_from_the_given_dir() {
+        let dir = std::env::temp_dir().join(format!("blute-headless-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        assert!(Context::headless(1, Some(&dir)).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+        // a dir that isn't there has no species
+        assert!(Context::headless(1, Some(&dir)).is_ok());
     }
 }
@@ -1,2 +1,6 @@
 pub mod acnh_flowers;
+pub mod breeding;
+pub mod custom;
 pub mod flower;
+pub mod inference;
+pub mod lineage;
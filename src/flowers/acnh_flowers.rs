@@ -1,14 +1,14 @@
-use std::str::FromStr;
+use std::rc::Rc;
 
 use crate::flowers::flower::{
-    ACNHFlowerType, Flower, FlowerContext, FlowerData, FlowerType, Phenotype,
+    ACNHFlowerType, AnyFlower, Flower, FlowerContext, FlowerData, FlowerType, Phenotype,
 };
 use crate::genetics::{GeneType, Genotype};
 
 use crate::AnsiColor;
 use crate::ui::terminal::TextBlueprint;
 
-fn get_acnh_flower_color(color_name: &str) -> Option<AnsiColor> {
+pub fn get_acnh_flower_color(color_name: &str) -> Option<AnsiColor> {
     match color_name {
         "Red" => Some(AnsiColor::from_code(160)),
         "White" => Some(AnsiColor::from_code(7)),
@@ -40,6 +40,10 @@ impl Flower for ACNHRose {
         self.0.clone()
     }
 
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
     fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
         let color_string = flower_context
             .get_phenotype_string(
@@ -73,6 +77,10 @@ impl Flower for ACNHMum {
         self.0.clone()
     }
 
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
     fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
         let color_string = flower_context
             .get_phenotype_string(
@@ -106,6 +114,10 @@ impl Flower for ACNHHyacinth {
         self.0.clone()
     }
 
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
     fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
         let color_string = flower_context
             .get_phenotype_string(
@@ -115,9 +127,231 @@ impl Flower for ACNHHyacinth {
                     .expect("could not get color index for acnh hyacinth"),
             )
             .expect("could not get color for acnh hyacinth");
-        let color =
-            get_acnh_flower_color(&color_string).expect("could not get ansi color for acnh hyacinth");
+        let color = get_acnh_flower_color(&color_string)
+            .expect("could not get ansi color for acnh hyacinth");
         let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
         Phenotype::new(blueprint, '⁑')
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ACNHTulip(pub Genotype);
+
+impl Flower for ACNHTulip {
+    fn info(&self) -> FlowerData {
+        let gene_print = vec![GeneType::Mendelian; 3];
+        FlowerData::new(
+            "Tulip (acnh)".to_string(),
+            gene_print,
+            FlowerType::ACNH(ACNHFlowerType::Tulip),
+        )
+    }
+
+    fn genotype(&self) -> Genotype {
+        self.0.clone()
+    }
+
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
+    fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
+        let color_string = flower_context
+            .get_phenotype_string(
+                self.info().flower_type(),
+                self.0
+                    .into_index()
+                    .expect("could not get color index for acnh tulip"),
+            )
+            .expect("could not get color for acnh tulip");
+        let color =
+            get_acnh_flower_color(&color_string).expect("could not get ansi color for acnh tulip");
+        let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
+        Phenotype::new(blueprint, '♆')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ACNHPansy(pub Genotype);
+
+impl Flower for ACNHPansy {
+    fn info(&self) -> FlowerData {
+        let gene_print = vec![GeneType::Mendelian; 3];
+        FlowerData::new(
+            "Pansy (acnh)".to_string(),
+            gene_print,
+            FlowerType::ACNH(ACNHFlowerType::Pansy),
+        )
+    }
+
+    fn genotype(&self) -> Genotype {
+        self.0.clone()
+    }
+
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
+    fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
+        let color_string = flower_context
+            .get_phenotype_string(
+                self.info().flower_type(),
+                self.0
+                    .into_index()
+                    .expect("could not get color index for acnh pansy"),
+            )
+            .expect("could not get color for acnh pansy");
+        let color =
+            get_acnh_flower_color(&color_string).expect("could not get ansi color for acnh pansy");
+        let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
+        Phenotype::new(blueprint, '✿')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ACNHCosmos(pub Genotype);
+
+impl Flower for ACNHCosmos {
+    fn info(&self) -> FlowerData {
+        let gene_print = vec![GeneType::Mendelian; 3];
+        FlowerData::new(
+            "Cosmos (acnh)".to_string(),
+            gene_print,
+            FlowerType::ACNH(ACNHFlowerType::Cosmos),
+        )
+    }
+
+    fn genotype(&self) -> Genotype {
+        self.0.clone()
+    }
+
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
+    fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
+        let color_string = flower_context
+            .get_phenotype_string(
+                self.info().flower_type(),
+                self.0
+                    .into_index()
+                    .expect("could not get color index for acnh cosmos"),
+            )
+            .expect("could not get color for acnh cosmos");
+        let color =
+            get_acnh_flower_color(&color_string).expect("could not get ansi color for acnh cosmos");
+        let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
+        Phenotype::new(blueprint, '✺')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ACNHLily(pub Genotype);
+
+impl Flower for ACNHLily {
+    fn info(&self) -> FlowerData {
+        let gene_print = vec![GeneType::Mendelian; 3];
+        FlowerData::new(
+            "Lily (acnh)".to_string(),
+            gene_print,
+            FlowerType::ACNH(ACNHFlowerType::Lily),
+        )
+    }
+
+    fn genotype(&self) -> Genotype {
+        self.0.clone()
+    }
+
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
+    fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
+        let color_string = flower_context
+            .get_phenotype_string(
+                self.info().flower_type(),
+                self.0
+                    .into_index()
+                    .expect("could not get color index for acnh lily"),
+            )
+            .expect("could not get color for acnh lily");
+        let color =
+            get_acnh_flower_color(&color_string).expect("could not get ansi color for acnh lily");
+        let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
+        Phenotype::new(blueprint, '⚜')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ACNHWindflower(pub Genotype);
+
+impl Flower for ACNHWindflower {
+    fn info(&self) -> FlowerData {
+        let gene_print = vec![GeneType::Mendelian; 3];
+        FlowerData::new(
+            "Windflower (acnh)".to_string(),
+            gene_print,
+            FlowerType::ACNH(ACNHFlowerType::Windflower),
+        )
+    }
+
+    fn genotype(&self) -> Genotype {
+        self.0.clone()
+    }
+
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
+    fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
+        let color_string = flower_context
+            .get_phenotype_string(
+                self.info().flower_type(),
+                self.0
+                    .into_index()
+                    .expect("could not get color index for acnh windflower"),
+            )
+            .expect("could not get color for acnh windflower");
+        let color = get_acnh_flower_color(&color_string)
+            .expect("could not get ansi color for acnh windflower");
+        let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
+        Phenotype::new(blueprint, '❀')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ACNHLilyOfTheValley(pub Genotype);
+
+impl Flower for ACNHLilyOfTheValley {
+    fn info(&self) -> FlowerData {
+        let gene_print = vec![GeneType::Mendelian; 1];
+        FlowerData::new(
+            "Lily of the valley (acnh)".to_string(),
+            gene_print,
+            FlowerType::ACNH(ACNHFlowerType::LilyOfTheValley),
+        )
+    }
+
+    fn genotype(&self) -> Genotype {
+        self.0.clone()
+    }
+
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        Rc::new(Self(genotype))
+    }
+
+    fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype {
+        let color_string = flower_context
+            .get_phenotype_string(
+                self.info().flower_type(),
+                self.0
+                    .into_index()
+                    .expect("could not get color index for acnh lily of the valley"),
+            )
+            .expect("could not get color for acnh lily of the valley");
+        let color = get_acnh_flower_color(&color_string)
+            .expect("could not get ansi color for acnh lily of the valley");
+        let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
+        Phenotype::new(blueprint, 'ǂ')
+    }
+}
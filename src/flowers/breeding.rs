@@ -0,0 +1,383 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::flowers::flower::{FlowerContext, FlowerType};
+use crate::genetics::Genotype;
+
+/// One cross in a breeding plan
+#[derive(Debug, Clone)]
+pub struct BreedingStep {
+    parents: (Genotype, Genotype),
+    child: Genotype,
+    color: String,
+    probability: f64,
+    flowers_needed: u32,
+}
+
+impl BreedingStep {
+    pub fn parents(&self) -> (Genotype, Genotype) {
+        self.parents.clone()
+    }
+
+    pub fn child(&self) -> Genotype {
+        self.child.clone()
+    }
+
+    pub fn color(&self) -> String {
+        self.color.clone()
+    }
+
+    /// The chance that a single cross of the parents gives the child
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// How many times the parents have to be crossed on average to get the child
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.probability
+    }
+
+    /// How many of the child the plan needs, 2 when a later step crosses it with itself
+    pub fn flowers_needed(&self) -> u32 {
+        self.flowers_needed
+    }
+}
+
+/// Every cross needed to get to a target flower, parents always coming before their children.
+/// Each child can be told apart from the other children of its cross by its color,
+/// so the plan can be followed without knowing any hidden genes
+#[derive(Debug, Clone)]
+pub struct BreedingPlan {
+    target: Genotype,
+    steps: Vec<BreedingStep>,
+}
+
+impl BreedingPlan {
+    pub fn target(&self) -> Genotype {
+        self.target.clone()
+    }
+
+    pub fn steps(&self) -> Vec<BreedingStep> {
+        self.steps.clone()
+    }
+
+    /// The total number of crosses the plan takes on average.
+    /// Flowers keep breeding after they've had children, so every step is only done once
+    pub fn expected_crosses(&self) -> f64 {
+        self.steps
+            .iter()
+            .map(|s| s.flowers_needed as f64 * s.expected_attempts())
+            .sum()
+    }
+}
+
+/// Where a genotype is bred from in a plan
+#[derive(Debug, Clone)]
+struct Origin {
+    parents: (Genotype, Genotype),
+    probability: f64,
+}
+
+/// Every bred genotype a plan needs and where it comes from. Seeds aren't in it
+type Recipe = HashMap<Genotype, Origin>;
+
+/// The children of a cross that no sibling shares a color with, so the player knows which one they got
+fn identifiable_children(
+    flower_context: &FlowerContext,
+    flower_type: FlowerType,
+    parents: (&Genotype, &Genotype),
+) -> Vec<(Genotype, f64)> {
+    let Some(children) = parents.0.offspring_distribution(parents.1) else {
+        return vec![];
+    };
+    let colors: Vec<Option<String>> = children
+        .iter()
+        .map(|(child, _)| flower_context.phenotype_name(flower_type, child))
+        .collect();
+    children
+        .iter()
+        .zip(colors.iter())
+        .filter(|(_, color)| color.is_some() && colors.iter().filter(|c| c == color).count() == 1)
+        .map(|(child, _)| child.clone())
+        .collect()
+}
+
+/// The bred genotypes a recipe needs to get to `target`, and how many of each: 2 if some step crosses it with itself
+fn needed(recipe: &Recipe, target: &Genotype) -> HashMap<Genotype, u32> {
+    let mut needed: HashMap<Genotype, u32> = HashMap::new();
+    let mut stack = vec![target.clone()];
+    while let Some(genotype) = stack.pop() {
+        let Some(origin) = recipe.get(&genotype) else {
+            continue;
+        };
+        if needed.contains_key(&genotype) {
+            continue;
+        }
+        needed.insert(genotype, 1);
+        stack.push(origin.parents.0.clone());
+        stack.push(origin.parents.1.clone());
+    }
+    let selfed: HashSet<Genotype> = needed
+        .keys()
+        .filter_map(|g| recipe.get(g))
+        .filter(|o| o.parents.0 == o.parents.1)
+        .map(|o| o.parents.0.clone())
+        .collect();
+    for (genotype, count) in needed.iter_mut() {
+        if selfed.contains(genotype) {
+            *count = 2;
+        }
+    }
+    needed
+}
+
+/// The crosses a recipe takes on average to get to `target`
+fn cost(recipe: &Recipe, target: &Genotype) -> f64 {
+    needed(recipe, target)
+        .iter()
+        .map(|(g, &count)| count as f64 / recipe[g].probability)
+        .sum()
+}
+
+/// Finds the ways to breed a flower of the given color starting from the shop seeds of its type,
+/// cheapest plan first. Only children that can be told apart from their siblings by color are bred from,
+/// so colors that need test crosses to get to have no plan. Empty if the color can't be reached
+pub fn plan_breeding(
+    flower_context: &FlowerContext,
+    flower_type: FlowerType,
+    color: &str,
+) -> Vec<BreedingPlan> {
+    // the cheapest known recipe for every genotype the player can get and recognize
+    let mut recipes: HashMap<Genotype, Recipe> = flower_context
+        .seeds_of(flower_type)
+        .iter()
+        .map(|f| (f.genotype(), Recipe::new()))
+        .collect();
+    let mut costs: HashMap<Genotype, f64> = recipes.keys().map(|g| (g.clone(), 0.0)).collect();
+
+    // keep relaxing the cost of every genotype until crossing known ones can't get anything cheaper
+    loop {
+        let mut known: Vec<Genotype> = recipes.keys().cloned().collect();
+        known.sort_by_key(|g| g.to_code());
+        let mut improved = false;
+
+        for (i, g1) in known.iter().enumerate() {
+            for g2 in known.iter().skip(i) {
+                for (child, p) in identifiable_children(flower_context, flower_type, (g1, g2)) {
+                    // a plan can't need the child to make one of its own ancestors
+                    if recipes[g1].contains_key(&child) || recipes[g2].contains_key(&child) {
+                        continue;
+                    }
+                    let mut recipe = recipes[g1].clone();
+                    for (g, origin) in recipes[g2].iter() {
+                        recipe.entry(g.clone()).or_insert_with(|| origin.clone());
+                    }
+                    recipe.insert(
+                        child.clone(),
+                        Origin {
+                            parents: (g1.clone(), g2.clone()),
+                            probability: p,
+                        },
+                    );
+
+                    let cost = cost(&recipe, &child);
+                    if costs.get(&child).is_none_or(|&old| cost < old - 1e-9) {
+                        costs.insert(child.clone(), cost);
+                        recipes.insert(child, recipe);
+                        improved = true;
+                    }
+                }
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    let mut plans: Vec<BreedingPlan> = recipes
+        .iter()
+        .filter(|(g, _)| flower_context.phenotype_name(flower_type, g).as_deref() == Some(color))
+        .map(|(target, recipe)| {
+            let needed = needed(recipe, target);
+            let mut steps = vec![];
+            collect_steps(
+                flower_context,
+                flower_type,
+                recipe,
+                &needed,
+                target,
+                &mut steps,
+            );
+            BreedingPlan {
+                target: target.clone(),
+                steps,
+            }
+        })
+        .collect();
+
+    plans.sort_by(|a, b| {
+        a.expected_crosses()
+            .total_cmp(&b.expected_crosses())
+            .then_with(|| a.target.to_code().cmp(&b.target.to_code()))
+    });
+    plans
+}
+
+/// Adds the steps to get a genotype after the steps to get its parents, skipping repeats
+fn collect_steps(
+    flower_context: &FlowerContext,
+    flower_type: FlowerType,
+    recipe: &Recipe,
+    needed: &HashMap<Genotype, u32>,
+    genotype: &Genotype,
+    steps: &mut Vec<BreedingStep>,
+) {
+    let Some(origin) = recipe.get(genotype) else {
+        // seeds don't need breeding
+        return;
+    };
+    if steps.iter().any(|s| s.child == *genotype) {
+        return;
+    }
+
+    collect_steps(
+        flower_context,
+        flower_type,
+        recipe,
+        needed,
+        &origin.parents.0,
+        steps,
+    );
+    collect_steps(
+        flower_context,
+        flower_type,
+        recipe,
+        needed,
+        &origin.parents.1,
+        steps,
+    );
+
+    steps.push(BreedingStep {
+        parents: origin.parents.clone(),
+        child: genotype.clone(),
+        color: flower_context
+            .phenotype_name(flower_type, genotype)
+            .unwrap_or_default(),
+        probability: origin.probability,
+        flowers_needed: needed.get(genotype).copied().unwrap_or(1),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::ACNHFlowerType;
+
+    fn cheapest(flower_type: ACNHFlowerType, color: &str) -> BreedingPlan {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        plan_breeding(&flower_context, FlowerType::ACNH(flower_type), color)
+            .into_iter()
+            .next()
+            .unwrap()
+    }
+
+    fn codes(step: &BreedingStep) -> (String, String, String) {
+        let (a, b) = step.parents();
+        (a.to_code(), b.to_code(), step.child().to_code())
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn blue_hyacinths_come_from_white_seeds() {
+        let plan = cheapest(ACNHFlowerType::Hyacinth, "Blue");
+        let steps = plan.steps();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(codes(&steps[0]), ("001".into(), "001".into(), "002".into()));
+        assert_close(steps[0].probability(), 0.25);
+        // both parents are seeds, so there's nothing to breed twice
+        assert_eq!(steps[0].flowers_needed(), 1);
+        assert_close(plan.expected_crosses(), 4.0);
+    }
+
+    #[test]
+    fn black_roses_come_from_red_seeds() {
+        let plan = cheapest(ACNHFlowerType::Rose, "Black");
+        let steps = plan.steps();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(
+            codes(&steps[0]),
+            ("2001".into(), "2001".into(), "2000".into())
+        );
+        assert_close(plan.expected_crosses(), 4.0);
+    }
+
+    #[test]
+    fn blue_roses_take_the_seed_route() {
+        let plan = cheapest(ACNHFlowerType::Rose, "Blue");
+        let steps: Vec<(String, String, String)> = plan.steps().iter().map(codes).collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("0010".into(), "0010".into(), "0020".into()),
+                ("0020".into(), "2001".into(), "1010".into()),
+                ("0200".into(), "1010".into(), "1110".into()),
+                ("1110".into(), "1110".into(), "2220".into()),
+            ]
+        );
+        // the hybrid red gets crossed with itself, so two of them are bred
+        assert_eq!(plan.steps()[2].flowers_needed(), 2);
+        assert_close(plan.expected_crosses(), 4.0 + 2.0 + 2.0 * 4.0 + 64.0);
+    }
+
+    #[test]
+    fn shared_steps_are_counted_once() {
+        let plan = cheapest(ACNHFlowerType::Cosmos, "Black");
+        let steps = plan.steps();
+        let children: HashSet<Genotype> = steps.iter().map(|s| s.child()).collect();
+        assert_eq!(children.len(), steps.len());
+        let parents_bred = steps
+            .iter()
+            .flat_map(|s| [s.parents().0, s.parents().1])
+            .filter(|p| children.contains(p))
+            .count();
+        // some bred flower is the parent of more than one later step
+        assert!(parents_bred > steps.len() - 1);
+        let total: f64 = steps
+            .iter()
+            .map(|s| s.flowers_needed() as f64 / s.probability())
+            .sum();
+        assert_close(plan.expected_crosses(), total);
+    }
+
+    #[test]
+    fn every_child_can_be_told_apart_by_color() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        for flower_type in flower_context.flower_types() {
+            for color in flower_context.colors_of(flower_type) {
+                for plan in plan_breeding(&flower_context, flower_type, &color) {
+                    for step in plan.steps() {
+                        let (a, b) = step.parents();
+                        let siblings = a.offspring_distribution(&b).unwrap();
+                        let same_color = siblings
+                            .iter()
+                            .filter(|(g, _)| {
+                                flower_context.phenotype_name(flower_type, g) == Some(step.color())
+                            })
+                            .count();
+                        assert_eq!(same_color, 1, "{:?} in {:?}", step, flower_type);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seeds_need_no_steps() {
+        let plan = cheapest(ACNHFlowerType::Rose, "Red");
+        assert!(plan.steps().is_empty());
+        assert_close(plan.expected_crosses(), 0.0);
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::from_reader;
+
+use crate::flowers::acnh_flowers::get_acnh_flower_color;
+use crate::flowers::flower::{
+    AnyFlower, Flower, FlowerContext, FlowerData, FlowerType, Phenotype, SEED_PRICE,
+};
+use crate::genetics::{GeneType, Genotype};
+use crate::ui::terminal::{AnsiColor, TextBlueprint};
+
+/// A species file exactly as it is written.
+/// Genotypes are written with `Genotype::to_code`, like "2o" for a Mendelian and a Bloodlike gene
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpeciesFile {
+    name: String,
+    glyph: char,
+    gene_print: Vec<GeneType>,
+    /// One letter per gene for allele notation, lowercase to write the gene the other way around.
+    /// Defaults to `Genotype::default_letters`
+    #[serde(default)]
+    gene_letters: Option<String>,
+    /// Color names and their 256-color terminal codes. The ACNH colors can be used without being listed
+    #[serde(default)]
+    palette: HashMap<String, u8>,
+    /// The color of every genotype
+    phenotypes: HashMap<String, String>,
+    seeds: Vec<String>,
+    /// What a bag of seeds costs in bells. Defaults to what ACNH seeds cost
+    #[serde(default)]
+    seed_price: Option<u32>,
+}
+
+/// A flower species designed outside of the code and loaded from a file
+#[derive(Debug, Clone)]
+pub struct CustomSpecies {
+    id: usize,
+    name: String,
+    glyph: char,
+    gene_print: Vec<GeneType>,
+    gene_letters: Vec<char>,
+    palette: HashMap<String, AnsiColor>,
+    phenotypes: HashMap<Genotype, String>,
+    seeds: Vec<Genotype>,
+    seed_price: u32,
+}
+
+impl CustomSpecies {
+    /// Where species files are loaded from: `$BLUTE_SPECIES_DIR`, or else `~/.config/blute/species`
+    pub fn default_dir() -> Option<PathBuf> {
+        if let Some(dir) = std::env::var_os("BLUTE_SPECIES_DIR") {
+            return Some(PathBuf::from(dir));
+        }
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config.join("blute").join("species"))
+    }
+
+    /// Loads every `.json` file in the directory, in file name order.
+    /// A directory that doesn't exist just has no species
+    pub fn load_dir(dir: &Path, first_id: usize) -> Result<Vec<Rc<CustomSpecies>>, Box<dyn Error>> {
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        paths.retain(|p| p.extension().is_some_and(|e| e == "json"));
+        paths.sort();
+
+        let mut species: Vec<Rc<CustomSpecies>> = vec![];
+        for (i, path) in paths.iter().enumerate() {
+            let s = Self::from_file(path, first_id + i)?;
+            if let Some(other) = species.iter().find(|o| o.name == s.name) {
+                return Err(format!(
+                    "{}: name: \"{}\" is already used by another species",
+                    path.display(),
+                    other.name
+                )
+                .into());
+            }
+            species.push(Rc::new(s));
+        }
+        Ok(species)
+    }
+
+    pub fn from_file(path: &Path, id: usize) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        let file: SpeciesFile =
+            from_reader(reader).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_species_file(file, id).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Checks everything in the file, the error saying which entry is wrong
+    fn from_species_file(file: SpeciesFile, id: usize) -> Result<Self, String> {
+        if file.name.trim().is_empty() {
+            return Err("name: can't be empty".to_string());
+        }
+        if file.gene_print.is_empty() {
+            return Err("gene_print: needs at least one gene".to_string());
+        }
+
+        let gene_letters: Vec<char> = match file.gene_letters {
+            Some(letters) => letters.chars().collect(),
+            None => Genotype::default_letters(file.gene_print.len()),
+        };
+        if gene_letters.len() != file.gene_print.len() {
+            return Err(format!(
+                "gene_letters: needs one letter for each of the {} genes",
+                file.gene_print.len()
+            ));
+        }
+        if let Some(c) = gene_letters.iter().find(|c| !c.is_ascii_alphabetic()) {
+            return Err(format!("gene_letters: '{}' isn't an ASCII letter", c));
+        }
+        // upper and lower case are the same gene written the other way around
+        if let Some((_, c)) = gene_letters
+            .iter()
+            .enumerate()
+            .find(|&(i, c)| gene_letters[..i].iter().any(|o| o.eq_ignore_ascii_case(c)))
+        {
+            return Err(format!(
+                "gene_letters: '{}' is used for more than one gene",
+                c
+            ));
+        }
+
+        let palette: HashMap<String, AnsiColor> = file
+            .palette
+            .into_iter()
+            .map(|(color, code)| (color, AnsiColor::from_code(code)))
+            .collect();
+
+        let mut phenotypes: HashMap<Genotype, String> = HashMap::new();
+        for (code, color) in file.phenotypes {
+            let genotype = Genotype::from_code(&file.gene_print, &code).ok_or(format!(
+                "phenotypes[\"{}\"]: not a genotype for gene print {:?}",
+                code, file.gene_print
+            ))?;
+            if !palette.contains_key(&color) && get_acnh_flower_color(&color).is_none() {
+                return Err(format!(
+                    "phenotypes[\"{}\"]: color \"{}\" isn't in the palette",
+                    code, color
+                ));
+            }
+            if phenotypes.insert(genotype, color).is_some() {
+                return Err(format!("phenotypes[\"{}\"]: listed more than once", code));
+            }
+        }
+        if let Some(missing) = Genotype::all(&file.gene_print)
+            .iter()
+            .find(|g| !phenotypes.contains_key(g))
+        {
+            return Err(format!(
+                "phenotypes: no color for genotype \"{}\"",
+                missing.to_code()
+            ));
+        }
+
+        if file.seeds.is_empty() {
+            return Err("seeds: needs at least one seed".to_string());
+        }
+        let seeds = file
+            .seeds
+            .iter()
+            .enumerate()
+            .map(|(i, code)| {
+                Genotype::from_code(&file.gene_print, code).ok_or(format!(
+                    "seeds[{}]: \"{}\" is not a genotype for gene print {:?}",
+                    i, code, file.gene_print
+                ))
+            })
+            .collect::<Result<Vec<Genotype>, String>>()?;
+
+        Ok(Self {
+            id,
+            name: file.name,
+            glyph: file.glyph,
+            gene_print: file.gene_print,
+            gene_letters,
+            palette,
+            phenotypes,
+            seeds,
+            seed_price: file.seed_price.unwrap_or(SEED_PRICE),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn flower_type(&self) -> FlowerType {
+        FlowerType::Custom(self.id)
+    }
+
+    pub fn gene_letters(&self) -> Vec<char> {
+        self.gene_letters.clone()
+    }
+
+    pub fn seed_price(&self) -> u32 {
+        self.seed_price
+    }
+
+    pub fn color_of(&self, genotype: &Genotype) -> Option<String> {
+        self.phenotypes.get(genotype).cloned()
+    }
+
+    pub fn ansi_color(&self, color: &str) -> Option<AnsiColor> {
+        self.palette
+            .get(color)
+            .copied()
+            .or_else(|| get_acnh_flower_color(color))
+    }
+
+    pub fn new_flower(self: &Rc<Self>, genotype: Genotype) -> AnyFlower {
+        Rc::new(CustomFlower {
+            species: Rc::clone(self),
+            genotype,
+        })
+    }
+
+    pub fn seeds(self: &Rc<Self>) -> Vec<AnyFlower> {
+        self.seeds
+            .iter()
+            .map(|g| self.new_flower(g.clone()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CustomFlower {
+    species: Rc<CustomSpecies>,
+    genotype: Genotype,
+}
+
+impl Flower for CustomFlower {
+    fn info(&self) -> FlowerData {
+        FlowerData::new(
+            self.species.name(),
+            self.species.gene_print.clone(),
+            self.species.flower_type(),
+        )
+    }
+
+    fn genotype(&self) -> Genotype {
+        self.genotype.clone()
+    }
+
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower {
+        self.species.new_flower(genotype)
+    }
+
+    fn phenotype(&self, _flower_context: &FlowerContext) -> Phenotype {
+        let color_string = self
+            .species
+            .color_of(&self.genotype)
+            .expect("could not get color for custom flower");
+        let color = self
+            .species
+            .ansi_color(&color_string)
+            .expect("could not get ansi color for custom flower");
+        let blueprint = TextBlueprint::new().with_text_color(color).to_owned();
+        Phenotype::new(blueprint, self.species.glyph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Map, Value, from_value, json};
+
+    /// A color for every genotype of the species in `species`
+    fn phenotypes() -> Map<String, Value> {
+        ["0", "1", "2"]
+            .iter()
+            .flat_map(|m| ["A", "a", "B", "b", "X", "o"].map(|b| format!("{}{}", m, b)))
+            .map(|code| (code, json!("Moss")))
+            .collect()
+    }
+
+    /// A valid two gene species, with `changes` written over it
+    fn species(changes: Value) -> Result<CustomSpecies, String> {
+        let mut file = json!({
+            "name": "Moss",
+            "glyph": "*",
+            "gene_print": ["Mendelian", "Bloodlike"],
+            "gene_letters": "Mb",
+            "palette": { "Moss": 64 },
+            "phenotypes": phenotypes(),
+            "seeds": ["0o", "2X"],
+        });
+        for (key, value) in changes.as_object().unwrap() {
+            file[key] = value.clone();
+        }
+        CustomSpecies::from_species_file(from_value(file).unwrap(), 0)
+    }
+
+    fn error(changes: Value) -> String {
+        species(changes).unwrap_err()
+    }
+
+    #[test]
+    fn a_valid_file_loads() {
+        let moss = species(json!({})).unwrap();
+        assert_eq!(moss.gene_letters(), vec!['M', 'b']);
+        assert_eq!(moss.seed_price(), SEED_PRICE);
+        let genotype = Genotype::from_code(&[GeneType::Mendelian, GeneType::Bloodlike], "1X");
+        assert_eq!(moss.color_of(&genotype.unwrap()).as_deref(), Some("Moss"));
+    }
+
+    #[test]
+    fn errors_name_the_bad_entry() {
+        assert_eq!(error(json!({ "name": " " })), "name: can't be empty");
+        assert_eq!(
+            error(json!({ "gene_print": [] })),
+            "gene_print: needs at least one gene"
+        );
+        assert_eq!(
+            error(json!({ "gene_letters": "M" })),
+            "gene_letters: needs one letter for each of the 2 genes"
+        );
+        assert_eq!(
+            error(json!({ "gene_letters": "Mä" })),
+            "gene_letters: 'ä' isn't an ASCII letter"
+        );
+        assert_eq!(
+            error(json!({ "gene_letters": "Mm" })),
+            "gene_letters: 'm' is used for more than one gene"
+        );
+        assert_eq!(
+            error(json!({ "seeds": [] })),
+            "seeds: needs at least one seed"
+        );
+        assert!(error(json!({ "seeds": ["0o", "3o"] })).starts_with("seeds[1]: \"3o\" is not"));
+    }
+
+    #[test]
+    fn phenotype_tables_must_be_complete() {
+        // a table made for a species with one gene
+        let one_gene = json!({ "0": "Moss", "1": "Moss", "2": "Moss" });
+        assert!(
+            error(json!({ "phenotypes": one_gene }))
+                .contains("not a genotype for gene print [Mendelian, Bloodlike]")
+        );
+
+        let mut missing = phenotypes();
+        missing.remove("2o");
+        assert_eq!(
+            error(json!({ "phenotypes": missing })),
+            "phenotypes: no color for genotype \"2o\""
+        );
+
+        let mut teal = phenotypes();
+        teal.insert("2o".to_string(), json!("Teal"));
+        assert_eq!(
+            error(json!({ "phenotypes": teal })),
+            "phenotypes[\"2o\"]: color \"Teal\" isn't in the palette"
+        );
+        // ACNH colors don't have to be in the palette
+        let mut red = phenotypes();
+        red.insert("2o".to_string(), json!("Red"));
+        assert!(species(json!({ "phenotypes": red })).is_ok());
+    }
+
+    #[test]
+    fn names_are_only_used_once_in_a_dir() {
+        let dir = std::env::temp_dir().join(format!("blute-custom-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = json!({
+            "name": "Moss",
+            "glyph": "*",
+            "gene_print": ["Mendelian"],
+            "phenotypes": { "0": "White", "1": "Red", "2": "Red" },
+            "seeds": ["0"],
+        });
+        fs::write(dir.join("a.json"), file.to_string()).unwrap();
+        fs::write(dir.join("b.json"), file.to_string()).unwrap();
+        let e = CustomSpecies::load_dir(&dir, 0).unwrap_err().to_string();
+        assert!(
+            e.contains("b.json: name: \"Moss\" is already used"),
+            "{}",
+            e
+        );
+
+        // and unknown fields are pointed out with the file
+        fs::remove_file(dir.join("b.json")).unwrap();
+        fs::write(
+            dir.join("a.json"),
+            file.to_string().replace("seeds", "sedes"),
+        )
+        .unwrap();
+        let e = CustomSpecies::load_dir(&dir, 0).unwrap_err().to_string();
+        assert!(e.contains("a.json: unknown field `sedes`"), "{}", e);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
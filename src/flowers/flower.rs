@@ -1,28 +1,72 @@
-use crate::flowers::acnh_flowers::{ACNHHyacinth, ACNHMum, ACNHRose};
-use crate::genetics::{Gene, GeneType, Genotype, MendelianGene};
-use crate::ui::terminal::{AnsiColor, AnsiEffect, TextBlueprint};
+use crate::flowers::acnh_flowers::{
+    ACNHCosmos, ACNHHyacinth, ACNHLily, ACNHLilyOfTheValley, ACNHMum, ACNHPansy, ACNHRose,
+    ACNHTulip, ACNHWindflower,
+};
+use crate::flowers::custom::CustomSpecies;
+use crate::genetics::{
+    Distribution, Gene, GeneType, Genotype, MendelianGene, ParseGenotypeError, merge_outcomes,
+};
+use crate::ui::terminal::TextBlueprint;
 
-use serde::Deserialize;
-use serde_json::de::from_reader;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use serde_json::from_str;
 use std::error::Error;
-use std::fs::File;
-use std::io::BufReader;
-use std::path::{self, Path};
+use std::path::Path;
 use std::rc::Rc;
 
 pub type AnyFlower = Rc<dyn Flower>;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ACNHFlowerType {
     Rose,
     Mum,
     Hyacinth,
+    Tulip,
+    Pansy,
+    Cosmos,
+    Lily,
+    Windflower,
+    LilyOfTheValley,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// What a bag of seeds costs in the shop, in bells
+pub const SEED_PRICE: u32 = 240;
+/// What a flower sells for if every genotype of its species had its color. Rarer colors sell for more
+pub const BASE_SELL_PRICE: u32 = 40;
+
+impl ACNHFlowerType {
+    /// The letter of each gene in allele notation.
+    /// The white gene is lowercase since its dominant alleles are the ones written as "w"
+    pub fn gene_letters(&self) -> Vec<char> {
+        match self {
+            ACNHFlowerType::Rose => vec!['R', 'Y', 'w', 'S'],
+            ACNHFlowerType::Mum | ACNHFlowerType::Hyacinth | ACNHFlowerType::Pansy => {
+                vec!['R', 'Y', 'w']
+            }
+            ACNHFlowerType::Tulip | ACNHFlowerType::Cosmos | ACNHFlowerType::Lily => {
+                vec!['R', 'Y', 'S']
+            }
+            ACNHFlowerType::Windflower => vec!['R', 'O', 'w'],
+            ACNHFlowerType::LilyOfTheValley => vec!['L'],
+        }
+    }
+
+    pub fn seed_price(&self) -> u32 {
+        match self {
+            // never sold in the game, only found on perfect islands, so they cost a lot more in the shop
+            ACNHFlowerType::LilyOfTheValley => SEED_PRICE * 10,
+            _ => SEED_PRICE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FlowerType {
     ACNH(ACNHFlowerType),
+    /// A species loaded from a file, by its place in the loaded species.
+    /// The place changes when species files do, so saves use the name instead
+    Custom(usize),
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +83,14 @@ impl Phenotype {
         }
     }
 
+    pub fn character(&self) -> char {
+        self.character
+    }
+
+    pub fn blueprint(&self) -> TextBlueprint {
+        self.blueprint.clone()
+    }
+
     pub fn to_string(&self) -> String {
         self.blueprint.apply(self.character.to_string().as_str())
     }
@@ -77,6 +129,8 @@ pub trait Flower {
     fn info(&self) -> FlowerData;
 
     fn genotype(&self) -> Genotype;
+    /// A flower of the same kind with a different genotype, e.g. the child of a cross
+    fn with_genotype(&self, genotype: Genotype) -> AnyFlower;
     fn phenotype(&self, flower_context: &FlowerContext) -> Phenotype;
     fn to_string(&self, flower_context: &FlowerContext) -> String {
         self.phenotype(flower_context).to_string()
@@ -89,12 +143,22 @@ pub trait Flower {
         self.genotype().can_cross(&other.genotype())
     }
 
-    fn cross(&self, other: &Self) -> Genotype
+    /// Every child genotype and its exact probability
+    fn offspring_distribution(&self, other: &Self) -> Distribution<Genotype>
     where
         Self: Sized,
     {
         self.genotype()
-            .cross_with(&other.genotype())
+            .offspring_distribution(&other.genotype())
+            .expect("Couldn't cross-breed flowers...")
+    }
+
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Genotype
+    where
+        Self: Sized,
+    {
+        self.genotype()
+            .cross_with(&other.genotype(), rng)
             .expect("Couldn't cross-breed flowers...")
     }
 }
@@ -114,7 +178,13 @@ impl std::fmt::Debug for dyn Flower {
 pub struct ACNHPhenotypes {
     acnh_rose: Vec<String>,
     acnh_mum: Vec<String>,
-    acnh_hyacinth: Vec<String>
+    acnh_hyacinth: Vec<String>,
+    acnh_tulip: Vec<String>,
+    acnh_pansy: Vec<String>,
+    acnh_cosmos: Vec<String>,
+    acnh_lily: Vec<String>,
+    acnh_windflower: Vec<String>,
+    acnh_lily_of_the_valley: Vec<String>,
 }
 
 impl ACNHPhenotypes {
@@ -128,6 +198,12 @@ impl ACNHPhenotypes {
             ACNHFlowerType::Rose => self.acnh_rose.get(index).cloned(),
             ACNHFlowerType::Mum => self.acnh_mum.get(index).cloned(),
             ACNHFlowerType::Hyacinth => self.acnh_hyacinth.get(index).cloned(),
+            ACNHFlowerType::Tulip => self.acnh_tulip.get(index).cloned(),
+            ACNHFlowerType::Pansy => self.acnh_pansy.get(index).cloned(),
+            ACNHFlowerType::Cosmos => self.acnh_cosmos.get(index).cloned(),
+            ACNHFlowerType::Lily => self.acnh_lily.get(index).cloned(),
+            ACNHFlowerType::Windflower => self.acnh_windflower.get(index).cloned(),
+            ACNHFlowerType::LilyOfTheValley => self.acnh_lily_of_the_valley.get(index).cloned(),
         }
     }
 }
@@ -135,7 +211,8 @@ impl ACNHPhenotypes {
 #[derive(Debug, Clone)]
 pub struct FlowerContext {
     acnh_phenotypes: ACNHPhenotypes,
-    seeds: Vec<Vec<AnyFlower>>
+    custom_species: Vec<Rc<CustomSpecies>>,
+    seeds: Vec<Vec<AnyFlower>>,
 }
 
 impl FlowerContext {
@@ -149,41 +226,303 @@ impl FlowerContext {
             vec![
                 Rc::new(ACNHRose(Genotype::new(vec![m_2, m_0, m_0, m_1]))),
                 Rc::new(ACNHRose(Genotype::new(vec![m_0, m_0, m_1, m_0]))),
-                Rc::new(ACNHRose(Genotype::new(vec![m_0, m_2, m_0, m_0])))
+                Rc::new(ACNHRose(Genotype::new(vec![m_0, m_2, m_0, m_0]))),
             ],
             // ACNH Mums
             vec![
                 Rc::new(ACNHMum(Genotype::new(vec![m_2, m_0, m_0]))),
                 Rc::new(ACNHMum(Genotype::new(vec![m_0, m_0, m_1]))),
-                Rc::new(ACNHMum(Genotype::new(vec![m_0, m_2, m_0])))
+                Rc::new(ACNHMum(Genotype::new(vec![m_0, m_2, m_0]))),
             ],
             // ACNH Hyacinths
             vec![
                 Rc::new(ACNHHyacinth(Genotype::new(vec![m_2, m_0, m_1]))),
                 Rc::new(ACNHHyacinth(Genotype::new(vec![m_0, m_0, m_1]))),
                 Rc::new(ACNHHyacinth(Genotype::new(vec![m_0, m_2, m_0]))),
-            ]
+            ],
+            // ACNH Tulips
+            vec![
+                Rc::new(ACNHTulip(Genotype::new(vec![m_2, m_0, m_1]))),
+                Rc::new(ACNHTulip(Genotype::new(vec![m_0, m_0, m_1]))),
+                Rc::new(ACNHTulip(Genotype::new(vec![m_0, m_2, m_0]))),
+            ],
+            // ACNH Pansies
+            vec![
+                Rc::new(ACNHPansy(Genotype::new(vec![m_2, m_0, m_0]))),
+                Rc::new(ACNHPansy(Genotype::new(vec![m_0, m_0, m_1]))),
+                Rc::new(ACNHPansy(Genotype::new(vec![m_0, m_2, m_0]))),
+            ],
+            // ACNH Cosmos
+            vec![
+                Rc::new(ACNHCosmos(Genotype::new(vec![m_2, m_0, m_1]))),
+                Rc::new(ACNHCosmos(Genotype::new(vec![m_0, m_0, m_1]))),
+                Rc::new(ACNHCosmos(Genotype::new(vec![m_0, m_2, m_1]))),
+            ],
+            // ACNH Lilies
+            vec![
+                Rc::new(ACNHLily(Genotype::new(vec![m_2, m_0, m_1]))),
+                Rc::new(ACNHLily(Genotype::new(vec![m_0, m_0, m_2]))),
+                Rc::new(ACNHLily(Genotype::new(vec![m_0, m_2, m_0]))),
+            ],
+            // ACNH Windflowers
+            vec![
+                Rc::new(ACNHWindflower(Genotype::new(vec![m_2, m_0, m_0]))),
+                Rc::new(ACNHWindflower(Genotype::new(vec![m_0, m_0, m_1]))),
+                Rc::new(ACNHWindflower(Genotype::new(vec![m_0, m_2, m_0]))),
+            ],
+            // ACNH Lilies of the valley, which only come in white since they don't cross-breed into new colors
+            vec![Rc::new(ACNHLilyOfTheValley(Genotype::new(vec![m_0])))],
         ]
     }
 
-    fn all_seeds() -> Vec<Vec<AnyFlower>> {
-        vec![Self::acnh_seeds()].iter().flatten().cloned().collect()
+    fn custom_seeds(custom_species: &[Rc<CustomSpecies>]) -> Vec<Vec<AnyFlower>> {
+        custom_species.iter().map(|s| s.seeds()).collect()
     }
 
+    fn all_seeds(custom_species: &[Rc<CustomSpecies>]) -> Vec<Vec<AnyFlower>> {
+        [Self::acnh_seeds(), Self::custom_seeds(custom_species)]
+            .iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Loads the custom species from `CustomSpecies::default_dir` too
     pub fn new() -> Result<Self, Box<dyn Error>> {
+        match CustomSpecies::default_dir() {
+            Some(dir) => Self::with_species_dir(&dir),
+            None => Self::acnh_only(),
+        }
+    }
+
+    /// Just the ACNH species, the same on every machine
+    pub fn acnh_only() -> Result<Self, Box<dyn Error>> {
+        Self::from_custom_species(vec![])
+    }
+
+    pub fn with_species_dir(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::from_custom_species(CustomSpecies::load_dir(dir, 0)?)
+    }
+
+    fn from_custom_species(custom_species: Vec<Rc<CustomSpecies>>) -> Result<Self, Box<dyn Error>> {
         Ok(Self {
             acnh_phenotypes: ACNHPhenotypes::new()?,
-            seeds: Self::all_seeds()
+            seeds: Self::all_seeds(&custom_species),
+            custom_species,
         })
     }
 
+    pub fn custom_species(&self) -> Vec<Rc<CustomSpecies>> {
+        self.custom_species.clone()
+    }
+
+    /// Only Some for ACNH flowers, custom ones aren't indexed
     pub fn get_phenotype_string(&self, flower_type: FlowerType, index: usize) -> Option<String> {
         match flower_type {
             FlowerType::ACNH(f) => self.acnh_phenotypes.get_color(f, index),
+            FlowerType::Custom(_) => None,
+        }
+    }
+
+    /// The color name of a genotype, e.g. "Red"
+    pub fn phenotype_name(&self, flower_type: FlowerType, genotype: &Genotype) -> Option<String> {
+        match flower_type {
+            FlowerType::ACNH(_) => self.get_phenotype_string(flower_type, genotype.into_index()?),
+            FlowerType::Custom(id) => self.custom_species.get(id)?.color_of(genotype),
+        }
+    }
+
+    /// The letter of each gene in allele notation, see `Gene::to_string`
+    pub fn gene_letters(&self, flower_type: FlowerType) -> Vec<char> {
+        match flower_type {
+            FlowerType::ACNH(f) => f.gene_letters(),
+            FlowerType::Custom(id) => self
+                .custom_species
+                .get(id)
+                .map_or(vec![], |s| s.gene_letters()),
+        }
+    }
+
+    pub fn seed_price(&self, flower_type: FlowerType) -> u32 {
+        match flower_type {
+            FlowerType::ACNH(f) => f.seed_price(),
+            FlowerType::Custom(id) => self
+                .custom_species
+                .get(id)
+                .map_or(SEED_PRICE, |s| s.seed_price()),
+        }
+    }
+
+    /// How many bells a flower sells for, more the rarer its color is
+    pub fn sell_price(&self, flower: &AnyFlower) -> u32 {
+        let flower_type = flower.info().flower_type();
+        let rarity = self
+            .phenotype_name(flower_type, &flower.genotype())
+            .and_then(|color| self.color_rarity(flower_type, &color))
+            .unwrap_or(1.0);
+        (BASE_SELL_PRICE as f64 / rarity).round() as u32
+    }
+
+    /// A flower of the given type with the given genotype.
+    /// None for unknown custom species, or a genotype with other genes than the species has
+    pub fn new_flower(&self, flower_type: FlowerType, genotype: Genotype) -> Option<AnyFlower> {
+        if self.gene_print(flower_type)? != genotype.gene_print() {
+            return None;
         }
+        Some(match flower_type {
+            FlowerType::ACNH(ACNHFlowerType::Rose) => Rc::new(ACNHRose(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::Mum) => Rc::new(ACNHMum(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::Hyacinth) => Rc::new(ACNHHyacinth(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::Tulip) => Rc::new(ACNHTulip(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::Pansy) => Rc::new(ACNHPansy(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::Cosmos) => Rc::new(ACNHCosmos(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::Lily) => Rc::new(ACNHLily(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::Windflower) => Rc::new(ACNHWindflower(genotype)),
+            FlowerType::ACNH(ACNHFlowerType::LilyOfTheValley) => {
+                Rc::new(ACNHLilyOfTheValley(genotype))
+            }
+            FlowerType::Custom(id) => self.custom_species.get(id)?.new_flower(genotype),
+        })
+    }
+
+    /// Rolls a genotype distribution up into the chance of each color, e.g. "Red 25%, Pink 50%, White 25%"
+    pub fn phenotype_distribution(
+        &self,
+        flower_type: FlowerType,
+        genotypes: &Distribution<Genotype>,
+    ) -> Option<Distribution<String>> {
+        let colors = genotypes
+            .iter()
+            .map(|(genotype, p)| Some((self.phenotype_name(flower_type, genotype)?, *p)))
+            .collect::<Option<Distribution<String>>>()?;
+        Some(merge_outcomes(colors))
     }
 
     pub fn get_seeds(&self) -> Vec<Vec<AnyFlower>> {
         self.seeds.clone()
     }
+
+    /// The shop seeds of a single flower type
+    pub fn seeds_of(&self, flower_type: FlowerType) -> Vec<AnyFlower> {
+        self.seeds
+            .iter()
+            .flatten()
+            .filter(|f| f.info().flower_type() == flower_type)
+            .cloned()
+            .collect()
+    }
+
+    /// Every flower type there are seeds for, in shop order
+    pub fn flower_types(&self) -> Vec<FlowerType> {
+        self.seeds
+            .iter()
+            .filter_map(|row| Some(row.first()?.info().flower_type()))
+            .collect()
+    }
+
+    /// Reads a genotype of the flower type in allele notation with its letters, or in binary shorthand
+    pub fn parse_genotype(
+        &self,
+        flower_type: FlowerType,
+        text: &str,
+    ) -> Result<Genotype, ParseGenotypeError> {
+        let gene_print = self.gene_print(flower_type).unwrap_or_default();
+        Genotype::parse_with(&gene_print, &self.gene_letters(flower_type), text)
+    }
+
+    /// The custom species with exactly this name
+    pub fn custom_flower_type(&self, name: &str) -> Option<FlowerType> {
+        self.custom_species
+            .iter()
+            .find(|s| s.name() == name)
+            .map(|s| s.flower_type())
+    }
+
+    /// Finds a species by its name without caring about case, spaces or the "(acnh)" tag,
+    /// so "rose", "Lily-of-the-valley" and "Rose (acnh)" all work
+    pub fn flower_type_by_name(&self, name: &str) -> Option<FlowerType> {
+        let simplify = |name: &str| {
+            name.trim_end_matches("(acnh)")
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(|c| c.to_lowercase())
+                .collect::<String>()
+        };
+        let name = simplify(name);
+        self.flower_types().into_iter().find(|&t| {
+            self.seeds_of(t)
+                .first()
+                .is_some_and(|seed| simplify(&seed.info().name()) == name)
+        })
+    }
+
+    /// Every genotype a flower type can have
+    pub fn all_genotypes(&self, flower_type: FlowerType) -> Vec<Genotype> {
+        self.gene_print(flower_type)
+            .map_or(vec![], |gene_print| Genotype::all(&gene_print))
+    }
+
+    /// Every color a flower type can have, in the order of its genotypes
+    pub fn colors_of(&self, flower_type: FlowerType) -> Vec<String> {
+        let mut colors: Vec<String> = vec![];
+        for genotype in self.all_genotypes(flower_type) {
+            if let Some(color) = self.phenotype_name(flower_type, &genotype)
+                && !colors.contains(&color)
+            {
+                colors.push(color);
+            }
+        }
+        colors
+    }
+
+    /// The share of all the genotypes of a flower type that have the color, so rarer colors are closer to 0
+    pub fn color_rarity(&self, flower_type: FlowerType, color: &str) -> Option<f64> {
+        let genotypes = self.all_genotypes(flower_type);
+        let matching = genotypes
+            .iter()
+            .filter(|g| self.phenotype_name(flower_type, g).as_deref() == Some(color))
+            .count();
+        if matching == 0 {
+            None
+        } else {
+            Some(matching as f64 / genotypes.len() as f64)
+        }
+    }
+
+    /// The gene print shared by every flower of a type, taken from its seeds
+    pub fn gene_print(&self, flower_type: FlowerType) -> Option<Vec<GeneType>> {
+        Some(self.seeds_of(flower_type).first()?.info().gene_print())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rarer_colors_sell_for_more() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let rose = FlowerType::ACNH(ACNHFlowerType::Rose);
+        let gene_print = flower_context.gene_print(rose).unwrap();
+        let mut prices: Vec<(f64, u32)> = Genotype::all(&gene_print)
+            .into_iter()
+            .map(|genotype| {
+                let flower = flower_context.new_flower(rose, genotype).unwrap();
+                let color = flower_context
+                    .phenotype_name(rose, &flower.genotype())
+                    .unwrap();
+                let rarity = flower_context.color_rarity(rose, &color).unwrap();
+                (rarity, flower_context.sell_price(&flower))
+            })
+            .collect();
+        prices.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // the rarest color is worth the most, and a color every flower had would be worth the base price
+        assert!(prices.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert!(prices[0].1 > prices[prices.len() - 1].1);
+        for (rarity, price) in prices {
+            assert_eq!(price, (BASE_SELL_PRICE as f64 / rarity).round() as u32);
+            assert!(price >= BASE_SELL_PRICE);
+        }
+    }
 }
@@ -0,0 +1,305 @@
+use crate::flowers::flower::{FlowerContext, FlowerType};
+use crate::genetics::{Distribution, Genotype, merge_outcomes};
+
+/// What we believe the hidden genotype of a flower is, as a probability for every genotype it could have
+#[derive(Debug, Clone)]
+pub struct GenotypeBelief {
+    flower_type: FlowerType,
+    distribution: Distribution<Genotype>,
+}
+
+impl GenotypeBelief {
+    /// A flower whose genotype is known for sure, like a shop seed
+    pub fn known(flower_type: FlowerType, genotype: Genotype) -> Self {
+        Self {
+            flower_type,
+            distribution: vec![(genotype, 1.0)],
+        }
+    }
+
+    /// A flower of the given color that came from nowhere in particular,
+    /// every genotype of that color being equally likely.
+    /// None if the flower type has no genotype of that color
+    pub fn from_unknown_origin(
+        flower_context: &FlowerContext,
+        flower_type: FlowerType,
+        color: &str,
+    ) -> Option<Self> {
+        let genotypes = Genotype::all(&flower_context.gene_print(flower_type)?);
+        let p = 1.0 / genotypes.len() as f64;
+        Self {
+            flower_type,
+            distribution: genotypes.into_iter().map(|g| (g, p)).collect(),
+        }
+        .observe_color(flower_context, color)
+    }
+
+    /// A flower of the given color bred from two parents.
+    /// None if the parents can't be crossed or can't make that color
+    pub fn from_parents(
+        flower_context: &FlowerContext,
+        parents: (&GenotypeBelief, &GenotypeBelief),
+        color: &str,
+    ) -> Option<Self> {
+        let (a, b) = parents;
+        if a.flower_type != b.flower_type {
+            return None;
+        }
+
+        let mut children = vec![];
+        for (g1, p1) in a.distribution.iter() {
+            for (g2, p2) in b.distribution.iter() {
+                children.extend(
+                    g1.offspring_distribution(g2)?
+                        .into_iter()
+                        .map(|(child, p)| (child, p * p1 * p2)),
+                );
+            }
+        }
+
+        Self {
+            flower_type: a.flower_type,
+            distribution: merge_outcomes(children),
+        }
+        .observe_color(flower_context, color)
+    }
+
+    /// Keeps only the genotypes of the given color, scaled back up so they add up to 1
+    fn observe_color(self, flower_context: &FlowerContext, color: &str) -> Option<Self> {
+        let flower_type = self.flower_type;
+        self.weighted(|g| {
+            if flower_context.phenotype_name(flower_type, g).as_deref() == Some(color) {
+                1.0
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Multiplies every genotype by its likelihood and normalizes. None if nothing is left
+    fn weighted(self, likelihood: impl Fn(&Genotype) -> f64) -> Option<Self> {
+        let weighted: Distribution<Genotype> = self
+            .distribution
+            .into_iter()
+            .map(|(g, p)| {
+                let w = likelihood(&g);
+                (g, p * w)
+            })
+            .filter(|(_, p)| *p > 0.0)
+            .collect();
+        let total: f64 = weighted.iter().map(|(_, p)| p).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            flower_type: self.flower_type,
+            distribution: weighted.into_iter().map(|(g, p)| (g, p / total)).collect(),
+        })
+    }
+
+    /// Starts test crossing the flower with a partner. None if they're different species
+    pub fn test_cross(&self, partner: &GenotypeBelief) -> Option<TestCross> {
+        if self.flower_type != partner.flower_type {
+            return None;
+        }
+        let joint = self
+            .distribution
+            .iter()
+            .flat_map(|(g, p_g)| {
+                partner
+                    .distribution
+                    .iter()
+                    .map(move |(h, p_h)| ((g.clone(), h.clone()), p_g * p_h))
+            })
+            .collect();
+        Some(TestCross {
+            flower_type: self.flower_type,
+            joint,
+        })
+    }
+
+    pub fn flower_type(&self) -> FlowerType {
+        self.flower_type
+    }
+
+    /// The posterior probability of every genotype still possible
+    pub fn distribution(&self) -> Distribution<Genotype> {
+        self.distribution.clone()
+    }
+
+    pub fn probability_of(&self, genotype: &Genotype) -> f64 {
+        self.distribution
+            .iter()
+            .find(|(g, _)| g == genotype)
+            .map_or(0.0, |(_, p)| *p)
+    }
+
+    pub fn most_likely(&self) -> Option<(Genotype, f64)> {
+        self.distribution
+            .iter()
+            .max_by(|(_, p1), (_, p2)| p1.total_cmp(p2))
+            .cloned()
+    }
+
+    /// `true` once only one genotype is left
+    pub fn is_certain(&self) -> bool {
+        self.distribution.len() == 1
+    }
+}
+
+/// What we believe about a flower and the partner it's being crossed with, together.
+/// Every child says something about both of them, so children of the same pair aren't
+/// independent evidence about the flower unless the partner's genotype is known
+#[derive(Debug, Clone)]
+pub struct TestCross {
+    flower_type: FlowerType,
+    joint: Distribution<(Genotype, Genotype)>,
+}
+
+impl TestCross {
+    /// Updates both beliefs after the pair had a child of the given color.
+    /// Returns `false` and keeps the old beliefs if no pair of genotypes we believe in could have made that child
+    pub fn record_child(&mut self, flower_context: &FlowerContext, child_color: &str) -> bool {
+        let flower_type = self.flower_type;
+        let weighted: Distribution<(Genotype, Genotype)> = self
+            .joint
+            .iter()
+            .filter_map(|((g, h), p)| {
+                let children = g.offspring_distribution(h)?;
+                let colors = flower_context.phenotype_distribution(flower_type, &children)?;
+                let p_color = colors
+                    .iter()
+                    .find(|(c, _)| c == child_color)
+                    .map_or(0.0, |(_, p)| *p);
+                Some(((g.clone(), h.clone()), p * p_color))
+            })
+            .filter(|(_, p)| *p > 0.0)
+            .collect();
+        let total: f64 = weighted.iter().map(|(_, p)| p).sum();
+        if total <= 0.0 {
+            return false;
+        }
+        self.joint = weighted
+            .into_iter()
+            .map(|(pair, p)| (pair, p / total))
+            .collect();
+        true
+    }
+
+    /// What we believe about the flower now
+    pub fn flower(&self) -> GenotypeBelief {
+        self.marginal(|(g, _)| g)
+    }
+
+    /// What we believe about the partner now
+    pub fn partner(&self) -> GenotypeBelief {
+        self.marginal(|(_, h)| h)
+    }
+
+    fn marginal(&self, pick: impl Fn(&(Genotype, Genotype)) -> &Genotype) -> GenotypeBelief {
+        GenotypeBelief {
+            flower_type: self.flower_type,
+            distribution: merge_outcomes(
+                self.joint.iter().map(|(pair, p)| (pick(pair).clone(), *p)),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::ACNHFlowerType;
+    use crate::genetics::GeneType;
+
+    const HYACINTH: FlowerType = FlowerType::ACNH(ACNHFlowerType::Hyacinth);
+
+    fn hyacinth(code: &str) -> Genotype {
+        Genotype::from_code(&[GeneType::Mendelian; 3], code).unwrap()
+    }
+
+    /// A white rryy hyacinth that's as likely to have one blue allele as none
+    fn maybe_blue_carrier() -> GenotypeBelief {
+        GenotypeBelief {
+            flower_type: HYACINTH,
+            distribution: vec![(hyacinth("000"), 0.5), (hyacinth("001"), 0.5)],
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn unknown_blue_hyacinth() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let belief =
+            GenotypeBelief::from_unknown_origin(&flower_context, HYACINTH, "Blue").unwrap();
+        // rryyww and RRYyWW are the only blue hyacinths
+        assert_eq!(belief.distribution().len(), 2);
+        assert_close(belief.probability_of(&hyacinth("002")), 0.5);
+        assert_close(belief.probability_of(&hyacinth("210")), 0.5);
+    }
+
+    #[test]
+    fn white_child_of_white_seeds() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let seed = GenotypeBelief::known(HYACINTH, hyacinth("001"));
+        let child = GenotypeBelief::from_parents(&flower_context, (&seed, &seed), "White").unwrap();
+        // a quarter of the children are blue, which leaves 1 part WW to 2 parts Ww
+        assert_close(child.probability_of(&hyacinth("000")), 1.0 / 3.0);
+        assert_close(child.probability_of(&hyacinth("001")), 2.0 / 3.0);
+        assert!(GenotypeBelief::from_parents(&flower_context, (&seed, &seed), "Red").is_none());
+    }
+
+    #[test]
+    fn a_blue_child_proves_both_parents_carry_blue() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let mut test = maybe_blue_carrier()
+            .test_cross(&maybe_blue_carrier())
+            .unwrap();
+        assert!(test.record_child(&flower_context, "Blue"));
+        assert!(test.flower().is_certain());
+        assert_close(test.flower().probability_of(&hyacinth("001")), 1.0);
+        assert_close(test.partner().probability_of(&hyacinth("001")), 1.0);
+        assert!(!test.record_child(&flower_context, "Red"));
+    }
+
+    #[test]
+    fn white_children_of_an_uncertain_partner() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let carrier = hyacinth("001");
+        let mut test = maybe_blue_carrier()
+            .test_cross(&maybe_blue_carrier())
+            .unwrap();
+
+        // only carrier x carrier can have a blue child, 1/4 of the time:
+        // P(carrier) = (1/4 + 1/4 * 3/4) / (1/2 + 1/4 + 1/4 * 3/4) = 7/15
+        test.record_child(&flower_context, "White");
+        assert_close(test.flower().probability_of(&carrier), 7.0 / 15.0);
+        // (1/4 + 1/4 * 9/16) / (1/2 + 1/4 + 1/4 * 9/16) = 25/57, not the 49/113 of treating both as independent
+        test.record_child(&flower_context, "White");
+        assert_close(test.flower().probability_of(&carrier), 25.0 / 57.0);
+
+        // white children can always be blamed on the partner not carrying blue,
+        // so the flower never gets below the 1/4 : 1/2 it has when the partner doesn't carry
+        for _ in 0..100 {
+            test.record_child(&flower_context, "White");
+        }
+        assert_close(test.flower().probability_of(&carrier), 1.0 / 3.0);
+        assert_close(test.partner().probability_of(&carrier), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn white_children_of_a_known_partner() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let partner = GenotypeBelief::known(HYACINTH, hyacinth("001"));
+        let mut test = maybe_blue_carrier().test_cross(&partner).unwrap();
+        // with the partner known each child is independent: P(carrier) = (3/4)^n / (1 + (3/4)^n)
+        test.record_child(&flower_context, "White");
+        test.record_child(&flower_context, "White");
+        assert_close(test.flower().probability_of(&hyacinth("001")), 9.0 / 25.0);
+        assert!(test.partner().is_certain());
+    }
+}
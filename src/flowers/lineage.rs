@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::flowers::flower::{AnyFlower, FlowerContext};
+use crate::save::SavedFlower;
+
+/// Where a flower came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lineage {
+    /// Unique to the flower, never reused even once it's gone
+    id: u64,
+    /// The IDs of the two flowers it was bred from. None for seeds, or for a parent that had no record
+    parents: (Option<u64>, Option<u64>),
+    /// 0 for seeds, and one more than the older of its parents for bred flowers
+    generation: u32,
+    /// The day it was planted or bred on
+    born_on: u32,
+}
+
+impl Lineage {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn parents(&self) -> (Option<u64>, Option<u64>) {
+        self.parents
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn born_on(&self) -> u32 {
+        self.born_on
+    }
+}
+
+/// A flower as it was recorded in the pedigree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PedigreeRecord {
+    #[serde(flatten)]
+    flower: SavedFlower,
+    lineage: Lineage,
+}
+
+impl PedigreeRecord {
+    pub fn lineage(&self) -> Lineage {
+        self.lineage
+    }
+
+    /// None if the species isn't known anymore
+    pub fn flower(&self, flower_context: &FlowerContext) -> Option<AnyFlower> {
+        self.flower.to_flower(flower_context).ok()
+    }
+}
+
+/// Every flower that has ever been planted or bred, so family trees can be traced after flowers are sold
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Pedigree {
+    next_id: u64,
+    records: BTreeMap<u64, PedigreeRecord>,
+}
+
+impl Pedigree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&PedigreeRecord> {
+        self.records.get(&id)
+    }
+
+    fn record(
+        &mut self,
+        flower: &AnyFlower,
+        parents: (Option<u64>, Option<u64>),
+        generation: u32,
+        day: u32,
+    ) -> Lineage {
+        let lineage = Lineage {
+            id: self.next_id,
+            parents,
+            generation,
+            born_on: day,
+        };
+        self.next_id += 1;
+        self.records.insert(
+            lineage.id,
+            PedigreeRecord {
+                flower: SavedFlower::from_flower(flower),
+                lineage,
+            },
+        );
+        lineage
+    }
+
+    /// Records a flower grown from a seed, or found without any record of where it came from
+    pub fn record_seed(&mut self, flower: &AnyFlower, day: u32) -> Lineage {
+        self.record(flower, (None, None), 0, day)
+    }
+
+    /// Records a flower bred from two others. Parents without a record count as seeds,
+    /// and aren't linked to
+    pub fn record_birth(
+        &mut self,
+        child: &AnyFlower,
+        parents: (Option<Lineage>, Option<Lineage>),
+        day: u32,
+    ) -> Lineage {
+        let generation = parents
+            .0
+            .iter()
+            .chain(parents.1.iter())
+            .map(|p| p.generation)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let parent_ids = (parents.0.map(|p| p.id), parents.1.map(|p| p.id));
+        self.record(child, parent_ids, generation, day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::{ACNHFlowerType, FlowerType};
+
+    #[test]
+    fn a_parent_without_a_record_keeps_the_other() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let rose = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose))[0].clone();
+        let mut pedigree = Pedigree::new();
+        let seed = pedigree.record_seed(&rose, 1);
+        let child = pedigree.record_birth(&rose, (None, Some(seed)), 2);
+        assert_eq!(child.parents(), (None, Some(seed.id())));
+        assert_eq!(child.generation(), 1);
+        let grandchild = pedigree.record_birth(&rose, (Some(child), Some(seed)), 3);
+        assert_eq!(grandchild.parents(), (Some(child.id()), Some(seed.id())));
+        assert_eq!(grandchild.generation(), 2);
+    }
+}
@@ -1,8 +1,34 @@
-use std::{rc::Rc, vec::IntoIter};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use rand::{distr::Uniform, prelude::*, rng};
+/// Every possible outcome of a cross paired with its exact probability.
+/// The probabilities of a distribution always add up to 1.
+pub type Distribution<T> = Vec<(T, f64)>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Adds together the probabilities of equal outcomes, keeping the order they first appear in
+pub fn merge_outcomes<T: PartialEq>(
+    outcomes: impl IntoIterator<Item = (T, f64)>,
+) -> Distribution<T> {
+    let mut merged: Distribution<T> = vec![];
+    for (outcome, p) in outcomes {
+        match merged.iter_mut().find(|(o, _)| *o == outcome) {
+            Some((_, total)) => *total += p,
+            None => merged.push((outcome, p)),
+        }
+    }
+    merged
+}
+
+/// Formats a distribution like "Red 25%, Pink 50%, White 25%"
+pub fn format_distribution<T: std::fmt::Display>(distribution: &Distribution<T>) -> String {
+    distribution
+        .iter()
+        .map(|(outcome, p)| format!("{} {}%", outcome, (p * 1000.0).round() / 10.0))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GeneType {
     Mendelian,
     Bloodlike,
@@ -10,12 +36,12 @@ pub enum GeneType {
 }
 
 impl GeneType {
-    pub fn get_random(&self, rng: &mut ThreadRng) -> Gene {
+    pub fn get_random(&self, rng: &mut impl Rng) -> Gene {
         Gene::random(*self, rng)
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Gene {
     Mendelian(MendelianGene),
     Bloodlike(BloodlikeGene),
@@ -23,7 +49,7 @@ pub enum Gene {
 }
 
 impl Gene {
-    pub fn random(gene_type: GeneType, rng: &mut ThreadRng) -> Gene {
+    pub fn random(gene_type: GeneType, rng: &mut impl Rng) -> Gene {
         match gene_type {
             GeneType::Mendelian => Self::Mendelian(MendelianGene::random(rng)),
             GeneType::Bloodlike => Self::Bloodlike(BloodlikeGene::random(rng)),
@@ -31,15 +57,24 @@ impl Gene {
         }
     }
 
+    /// Every gene of the given type
+    pub fn all(gene_type: GeneType) -> Vec<Gene> {
+        match gene_type {
+            GeneType::Mendelian => MendelianGene::ALL.map(Self::Mendelian).to_vec(),
+            GeneType::Bloodlike => BloodlikeGene::ALL.map(Self::Bloodlike).to_vec(),
+            GeneType::Quadruplet => QuadrupletGene::ALL.map(Self::Quadruplet).to_vec(),
+        }
+    }
+
     pub fn gene_type(&self) -> GeneType {
         match self {
             Gene::Mendelian(_) => GeneType::Mendelian,
-            Gene::Bloodlike(_) => GeneType::Mendelian,
+            Gene::Bloodlike(_) => GeneType::Bloodlike,
             Gene::Quadruplet(_) => GeneType::Quadruplet,
         }
     }
 
-    pub fn cross_with(&self, other: &Self, rng: &mut ThreadRng) -> Option<Self> {
+    pub fn cross_with(&self, other: &Self, rng: &mut impl Rng) -> Option<Self> {
         match self {
             Gene::Mendelian(g) => {
                 if let Gene::Mendelian(o) = other {
@@ -65,6 +100,103 @@ impl Gene {
         }
     }
 
+    /// Every child gene the two genes can make, with its exact probability.
+    /// None if the genes aren't of the same type
+    pub fn offspring_distribution(&self, other: &Self) -> Option<Distribution<Self>> {
+        match (self, other) {
+            (Gene::Mendelian(g), Gene::Mendelian(o)) => Some(
+                g.offspring_distribution(o)
+                    .into_iter()
+                    .map(|(g, p)| (Gene::Mendelian(g), p))
+                    .collect(),
+            ),
+            (Gene::Bloodlike(g), Gene::Bloodlike(o)) => Some(
+                g.offspring_distribution(o)
+                    .into_iter()
+                    .map(|(g, p)| (Gene::Bloodlike(g), p))
+                    .collect(),
+            ),
+            (Gene::Quadruplet(g), Gene::Quadruplet(o)) => Some(
+                g.offspring_distribution(o)
+                    .into_iter()
+                    .map(|(g, p)| (Gene::Quadruplet(g), p))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// The gene in allele notation with the given letter, e.g. "Rr", "IaIb" or "AAaa".
+    /// A lowercase letter means the gene is written the other way around, with its dominant
+    /// alleles as lowercase letters, like the white gene of ACNH flowers
+    pub fn to_string(&self, c: char) -> String {
+        match self {
+            Gene::Mendelian(m) if c.is_lowercase() => m.flipped().to_string(c),
+            Gene::Mendelian(m) => m.to_string(c),
+            Gene::Bloodlike(b) => b.to_string(),
+            Gene::Quadruplet(q) if c.is_lowercase() => q.flipped().to_string(c),
+            Gene::Quadruplet(q) => q.to_string(c),
+        }
+    }
+
+    /// A single character standing for the gene, e.g. in species files.
+    /// Mendelian and Quadruplet genes use how many dominant alleles they have,
+    /// Bloodlike genes use A (IaIa), a (Iai), B (IbIb), b (Ibi), X (IaIb) and o (ii)
+    pub fn to_code(&self) -> char {
+        match self {
+            Gene::Mendelian(m) => char::from_digit(m.into_usize() as u32, 10).unwrap_or('?'),
+            Gene::Bloodlike(b) => match b {
+                BloodlikeGene::BloodHomozygousA => 'A',
+                BloodlikeGene::BloodHeterozygousA => 'a',
+                BloodlikeGene::BloodHomozygousB => 'B',
+                BloodlikeGene::BloodHeterozygousB => 'b',
+                BloodlikeGene::BloodAB => 'X',
+                BloodlikeGene::BloodO => 'o',
+            },
+            Gene::Quadruplet(q) => {
+                let dominant = q.to_alleles().iter().filter(|&&b| b).count();
+                char::from_digit(dominant as u32, 10).unwrap_or('?')
+            }
+        }
+    }
+
+    /// Reads a gene written like `to_string` from the start of `s`, giving back the gene and how many bytes it took.
+    /// The alleles of Mendelian and Quadruplet genes can be in any order, like "rR".
+    /// None for letters that aren't ASCII, since only those have a case to tell dominant from recessive
+    pub fn parse_prefix(gene_type: GeneType, c: char, s: &str) -> Option<(Gene, usize)> {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        let allele_count = match gene_type {
+            GeneType::Mendelian => 2,
+            GeneType::Quadruplet => 4,
+            GeneType::Bloodlike => {
+                return Self::all(gene_type)
+                    .into_iter()
+                    .map(|g| (g, g.to_string(c).len()))
+                    .filter(|(g, _)| s.starts_with(&g.to_string(c)))
+                    .max_by_key(|&(_, len)| len);
+            }
+        };
+
+        let alleles: Vec<char> = s.chars().take(allele_count).collect();
+        if alleles.len() != allele_count || !alleles.iter().all(|a| a.eq_ignore_ascii_case(&c)) {
+            return None;
+        }
+        let uppercase = |text: &str| text.chars().filter(|a| a.is_uppercase()).count();
+        let written = uppercase(&alleles.iter().collect::<String>());
+        let len = alleles.len();
+        Self::all(gene_type)
+            .into_iter()
+            .find(|g| uppercase(&g.to_string(c)) == written)
+            .map(|g| (g, len))
+    }
+
+    /// The gene of the given type written as `c`, see `to_code`
+    pub fn from_code(gene_type: GeneType, c: char) -> Option<Gene> {
+        Self::all(gene_type).into_iter().find(|g| g.to_code() == c)
+    }
+
     /// Only Some for Mendelian genes
     pub fn into_usize(&self) -> Option<usize> {
         if let Self::Mendelian(m) = *self {
@@ -77,7 +209,7 @@ impl Gene {
 
 /// Boring genes
 /// AA Aa aa
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MendelianGene {
     HomozygousRecessive,
     Heterozygous,
@@ -85,6 +217,12 @@ pub enum MendelianGene {
 }
 
 impl MendelianGene {
+    pub const ALL: [MendelianGene; 3] = [
+        MendelianGene::HomozygousRecessive,
+        MendelianGene::Heterozygous,
+        MendelianGene::HomozygousDominant,
+    ];
+
     pub const CHOOSE_TABLE: [MendelianGene; 4] = [
         MendelianGene::Heterozygous,
         MendelianGene::Heterozygous,
@@ -116,13 +254,13 @@ impl MendelianGene {
         }
     }
 
-    fn random(rng: &mut ThreadRng) -> Self {
+    fn random(rng: &mut impl Rng) -> Self {
         *Self::CHOOSE_TABLE
             .choose(rng)
             .expect("Could not choose random Mendelian Gene")
     }
 
-    fn cross(&self, other: &Self, rng: &mut ThreadRng) -> Self
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self
     where
         Self: Sized,
     {
@@ -137,6 +275,22 @@ impl MendelianGene {
         Self::from_bools([b1, b2])
     }
 
+    /// Each parent passes on one of its two alleles with equal chance
+    pub fn offspring_distribution(&self, other: &Self) -> Distribution<Self> {
+        merge_outcomes(self.to_bools().iter().flat_map(|&b1| {
+            other
+                .to_bools()
+                .into_iter()
+                .map(move |b2| (Self::from_bools([b1, b2]), 0.25))
+        }))
+    }
+
+    /// The same gene with its dominant and recessive alleles swapped
+    pub fn flipped(&self) -> Self {
+        let [b1, b2] = self.to_bools();
+        Self::from_bools([!b1, !b2])
+    }
+
     pub fn to_string(&self, c: char) -> String {
         let [b1, b2] = self.to_bools();
         let mut s = String::new();
@@ -165,7 +319,7 @@ enum BloodlikeAllele {
 
 /// Blood type-esque genes
 /// IaIa Iai IbIb Ibi IaIb ii
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BloodlikeGene {
     BloodHomozygousA,
     BloodHeterozygousA,
@@ -176,6 +330,15 @@ pub enum BloodlikeGene {
 }
 
 impl BloodlikeGene {
+    pub const ALL: [BloodlikeGene; 6] = [
+        BloodlikeGene::BloodHomozygousA,
+        BloodlikeGene::BloodHeterozygousA,
+        BloodlikeGene::BloodHomozygousB,
+        BloodlikeGene::BloodHeterozygousB,
+        BloodlikeGene::BloodAB,
+        BloodlikeGene::BloodO,
+    ];
+
     pub const CHOOSE_TABLE: [BloodlikeGene; 9] = [
         BloodlikeGene::BloodHomozygousA,
         BloodlikeGene::BloodHeterozygousA,
@@ -211,18 +374,19 @@ impl BloodlikeGene {
         match alleles {
             [BloodlikeAllele::AntigenA, BloodlikeAllele::AntigenA] => Self::BloodHomozygousA,
             [BloodlikeAllele::AntigenB, BloodlikeAllele::AntigenB] => Self::BloodHomozygousB,
-            [BloodlikeAllele::AntigenA, BloodlikeAllele::AntigenB] => Self::BloodAB,
+            [BloodlikeAllele::AntigenA, BloodlikeAllele::AntigenB]
+            | [BloodlikeAllele::AntigenB, BloodlikeAllele::AntigenA] => Self::BloodAB,
             [BloodlikeAllele::NoAntigen, BloodlikeAllele::NoAntigen] => Self::BloodO,
             [_, BloodlikeAllele::AntigenA] | [BloodlikeAllele::AntigenA, _] => {
                 Self::BloodHeterozygousA
             }
             [_, BloodlikeAllele::AntigenB] | [BloodlikeAllele::AntigenB, _] => {
-                Self::BloodHeterozygousA
+                Self::BloodHeterozygousB
             }
         }
     }
 
-    fn random(rng: &mut ThreadRng) -> Self
+    fn random(rng: &mut impl Rng) -> Self
     where
         Self: Sized,
     {
@@ -231,7 +395,19 @@ impl BloodlikeGene {
             .expect("Could not choose random bloodlike gene")
     }
 
-    fn cross(&self, other: &Self, rng: &mut ThreadRng) -> Self
+    pub fn to_string(&self) -> String {
+        match self {
+            BloodlikeGene::BloodHomozygousA => "IaIa",
+            BloodlikeGene::BloodHeterozygousA => "Iai",
+            BloodlikeGene::BloodHomozygousB => "IbIb",
+            BloodlikeGene::BloodHeterozygousB => "Ibi",
+            BloodlikeGene::BloodAB => "IaIb",
+            BloodlikeGene::BloodO => "ii",
+        }
+        .to_string()
+    }
+
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self
     where
         Self: Sized,
     {
@@ -245,11 +421,21 @@ impl BloodlikeGene {
             .expect("Could not choose random bloodlike allele");
         Self::from_alleles([a1, a2])
     }
+
+    /// Each parent passes on one of its two alleles with equal chance
+    pub fn offspring_distribution(&self, other: &Self) -> Distribution<Self> {
+        merge_outcomes(self.to_alleles().iter().flat_map(|&a1| {
+            other
+                .to_alleles()
+                .into_iter()
+                .map(move |a2| (Self::from_alleles([a1, a2]), 0.25))
+        }))
+    }
 }
 
 /// Fictitious (probably)
 /// AAAA AAAa AAaa Aaaa aaaa
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum QuadrupletGene {
     HomozygousDominant,
     SemihomozygousDominant,
@@ -259,6 +445,14 @@ pub enum QuadrupletGene {
 }
 
 impl QuadrupletGene {
+    pub const ALL: [QuadrupletGene; 5] = [
+        QuadrupletGene::HomozygousDominant,
+        QuadrupletGene::SemihomozygousDominant,
+        QuadrupletGene::Heterozygous,
+        QuadrupletGene::SemihomozygousRecessive,
+        QuadrupletGene::HomozygousRecessive,
+    ];
+
     pub fn to_alleles(&self) -> [bool; 4] {
         match self {
             QuadrupletGene::HomozygousDominant => [true, true, true, true],
@@ -271,16 +465,34 @@ impl QuadrupletGene {
 
     pub fn from_alleles(alleles: [bool; 4]) -> Self {
         match alleles.iter().map(|&b| b as u8).sum() {
-            0 => QuadrupletGene::HomozygousDominant,
-            1 => QuadrupletGene::SemihomozygousDominant,
+            0 => QuadrupletGene::HomozygousRecessive,
+            1 => QuadrupletGene::SemihomozygousRecessive,
             2 => QuadrupletGene::Heterozygous,
-            3 => QuadrupletGene::SemihomozygousRecessive,
-            4 => QuadrupletGene::HomozygousRecessive,
+            3 => QuadrupletGene::SemihomozygousDominant,
+            4 => QuadrupletGene::HomozygousDominant,
             _ => panic!("More than 4 booleans in [bool; 4]..."),
         }
     }
 
-    pub fn cross(&self, other: &Self, rng: &mut ThreadRng) -> Self
+    /// The same gene with its dominant and recessive alleles swapped
+    pub fn flipped(&self) -> Self {
+        Self::from_alleles(self.to_alleles().map(|b| !b))
+    }
+
+    pub fn to_string(&self, c: char) -> String {
+        self.to_alleles()
+            .iter()
+            .map(|&b| {
+                if b {
+                    c.to_ascii_uppercase()
+                } else {
+                    c.to_ascii_lowercase()
+                }
+            })
+            .collect()
+    }
+
+    pub fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self
     where
         Self: Sized,
     {
@@ -300,7 +512,25 @@ impl QuadrupletGene {
         )
     }
 
-    pub fn random(rng: &mut ThreadRng) -> Self
+    /// The 6 ways of picking 2 of the 4 alleles, which are what a parent can pass on
+    fn allele_pairs(&self) -> Vec<[bool; 2]> {
+        let alleles = self.to_alleles();
+        (0..4)
+            .flat_map(|i| ((i + 1)..4).map(move |j| [alleles[i], alleles[j]]))
+            .collect()
+    }
+
+    /// Each parent passes on one of its 6 allele pairs with equal chance
+    pub fn offspring_distribution(&self, other: &Self) -> Distribution<Self> {
+        let other_pairs = other.allele_pairs();
+        merge_outcomes(self.allele_pairs().into_iter().flat_map(|[a, b]| {
+            other_pairs
+                .iter()
+                .map(move |&[c, d]| (Self::from_alleles([a, b, c, d]), 1.0 / 36.0))
+        }))
+    }
+
+    pub fn random(rng: &mut impl Rng) -> Self
     where
         Self: Sized,
     {
@@ -315,11 +545,116 @@ impl QuadrupletGene {
 }
 
 /// All of the genes in a flower
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Genotype {
     genes: Vec<Gene>,
 }
 
+/// Why text couldn't be read as a genotype
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGenotypeError {
+    text: String,
+    /// The byte the problem starts at
+    position: usize,
+    reason: String,
+}
+
+impl ParseGenotypeError {
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl std::fmt::Display for ParseGenotypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "can't read \"{}\" as a genotype at character {}: {}",
+            self.text,
+            self.text[..self.position].chars().count() + 1,
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for ParseGenotypeError {}
+
+/// Writes the genotype with `Genotype::default_letters`, like "AaBBcc".
+/// Species that use their own letters are written with `to_allele_string`
+impl std::fmt::Display for Genotype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.to_allele_string(&Self::default_letters(self.genes.len()))
+        )
+    }
+}
+
+/// Reads a genotype without knowing its species, so the gene types come from how each gene is written:
+/// two of a letter like "Rr" is Mendelian, four like "AAaa" is Quadruplet, and "IaIb", "Iai", "ii" and so on are Bloodlike.
+/// Uppercase letters are always dominant, use `Genotype::parse_with` for species that write a gene the other way around.
+/// Anything with a digit is binary shorthand for Mendelian genes, like "2001"
+impl std::str::FromStr for Genotype {
+    type Err = ParseGenotypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |position: usize, reason: String| ParseGenotypeError {
+            text: s.to_string(),
+            position,
+            reason,
+        };
+        if s.is_empty() {
+            return Err(error(0, "there are no genes".to_string()));
+        }
+
+        if s.chars().any(|c| c.is_ascii_digit()) {
+            let gene_count = s.chars().count();
+            let gene_print = vec![GeneType::Mendelian; gene_count];
+            return Self::parse_with(&gene_print, &Self::default_letters(gene_count), s);
+        }
+
+        let mut position = 0;
+        let mut genes = vec![];
+        while position < s.len() {
+            let rest = &s[position..];
+            if let Some((gene, len)) = Gene::parse_prefix(GeneType::Bloodlike, 'I', rest) {
+                genes.push(gene);
+                position += len;
+                continue;
+            }
+
+            let c = rest.chars().next().expect("rest isn't empty");
+            if !c.is_ascii_alphabetic() {
+                return Err(error(position, format!("'{}' isn't an allele", c)));
+            }
+            let run = rest
+                .chars()
+                .take_while(|a| a.eq_ignore_ascii_case(&c))
+                .count();
+            let gene_type = match run {
+                2 => GeneType::Mendelian,
+                4 => GeneType::Quadruplet,
+                _ => {
+                    return Err(error(
+                        position,
+                        format!(
+                            "a gene has 2 or 4 alleles, but there are {} {}s",
+                            run,
+                            c.to_ascii_uppercase()
+                        ),
+                    ));
+                }
+            };
+            let (gene, len) = Gene::parse_prefix(gene_type, c.to_ascii_uppercase(), rest)
+                .expect("the run has the right number of alleles");
+            genes.push(gene);
+            position += len;
+        }
+        Ok(Genotype { genes })
+    }
+}
+
 impl FromIterator<Gene> for Genotype {
     fn from_iter<T: IntoIterator<Item = Gene>>(iter: T) -> Self {
         let genes = iter.into_iter().collect();
@@ -332,13 +667,33 @@ impl Genotype {
         Genotype { genes }
     }
 
-    pub fn new_random(gene_print: Vec<GeneType>) -> Self {
-        let mut rng = rng();
-        let genotype = gene_print.iter().map(|t| t.get_random(&mut rng)).collect();
+    pub fn new_random(gene_print: Vec<GeneType>, rng: &mut impl Rng) -> Self {
+        let genotype = gene_print.iter().map(|t| t.get_random(rng)).collect();
 
         Self { genes: genotype }
     }
 
+    /// Every genotype with the given gene print
+    pub fn all(gene_print: &[GeneType]) -> Vec<Self> {
+        gene_print
+            .iter()
+            .fold(vec![vec![]], |genotypes: Vec<Vec<Gene>>, &gene_type| {
+                genotypes
+                    .iter()
+                    .flat_map(|genes| {
+                        Gene::all(gene_type).into_iter().map(move |gene| {
+                            let mut genes = genes.clone();
+                            genes.push(gene);
+                            genes
+                        })
+                    })
+                    .collect()
+            })
+            .into_iter()
+            .map(Self::new)
+            .collect()
+    }
+
     pub fn gene_print(&self) -> Vec<GeneType> {
         self.genes.iter().map(|g| g.gene_type()).collect()
     }
@@ -347,12 +702,183 @@ impl Genotype {
         self.gene_print() == other.gene_print()
     }
 
-    pub fn cross_with(&self, other: &Self) -> Option<Self> {
-        let mut rng = rng();
+    pub fn cross_with(&self, other: &Self, rng: &mut impl Rng) -> Option<Self> {
+        self.genes
+            .iter()
+            .zip(other.genes.iter())
+            .map(|(&g1, g2)| g1.cross_with(g2, rng))
+            .collect()
+    }
+
+    /// Every child genotype the two genotypes can make, with its exact probability.
+    /// None if the genotypes can't be crossed
+    pub fn offspring_distribution(&self, other: &Self) -> Option<Distribution<Self>> {
+        if !self.can_cross(other) {
+            return None;
+        }
+
         self.genes
             .iter()
             .zip(other.genes.iter())
-            .map(|(&g1, g2)| g1.cross_with(g2, &mut rng))
+            .try_fold(vec![(vec![], 1.0)], |children, (g1, g2)| {
+                let genes = g1.offspring_distribution(g2)?;
+                Some(
+                    children
+                        .iter()
+                        .flat_map(|(child, p1)| {
+                            genes.iter().map(move |&(gene, p2)| {
+                                let mut child: Vec<Gene> = child.clone();
+                                child.push(gene);
+                                (child, p1 * p2)
+                            })
+                        })
+                        .collect::<Distribution<Vec<Gene>>>(),
+                )
+            })
+            .map(|children| {
+                children
+                    .into_iter()
+                    .map(|(genes, p)| (Self::new(genes), p))
+                    .collect()
+            })
+    }
+
+    /// Every gene in allele notation with its letter, like "RRyyWWSs" for a red rose seed
+    pub fn to_allele_string(&self, letters: &[char]) -> String {
+        self.genes
+            .iter()
+            .zip(letters.iter())
+            .map(|(g, &c)| g.to_string(c))
+            .collect()
+    }
+
+    /// The letters `Display` writes genes with: A, B, C... skipping I, which Bloodlike genes use
+    pub fn default_letters(gene_count: usize) -> Vec<char> {
+        ('A'..='Z')
+            .filter(|&c| c != 'I')
+            .cycle()
+            .take(gene_count)
+            .collect()
+    }
+
+    /// Reads a genotype of a species, written in allele notation with the species' letters like "RRyyWWSs",
+    /// or in binary shorthand like "2001"
+    pub fn parse_with(
+        gene_print: &[GeneType],
+        letters: &[char],
+        s: &str,
+    ) -> Result<Self, ParseGenotypeError> {
+        let error = |position: usize, reason: String| ParseGenotypeError {
+            text: s.to_string(),
+            position,
+            reason,
+        };
+        if letters.len() != gene_print.len() {
+            return Err(error(
+                0,
+                format!(
+                    "the species has {} genes but {} letters",
+                    gene_print.len(),
+                    letters.len()
+                ),
+            ));
+        }
+
+        // allele notation never has digits, and every gene takes at least two characters in it,
+        // so one valid code per gene can only be shorthand
+        let is_shorthand = s.chars().any(|c| c.is_ascii_digit())
+            || (s.chars().count() == gene_print.len()
+                && gene_print
+                    .iter()
+                    .zip(s.chars())
+                    .all(|(&t, c)| Gene::from_code(t, c).is_some()));
+        if is_shorthand {
+            let count = s.chars().count();
+            if count != gene_print.len() {
+                let position = s
+                    .char_indices()
+                    .nth(gene_print.len())
+                    .map_or(s.len(), |(i, _)| i);
+                return Err(error(
+                    position,
+                    format!(
+                        "shorthand has one character for each of the {} genes, not {}",
+                        gene_print.len(),
+                        count
+                    ),
+                ));
+            }
+            return gene_print
+                .iter()
+                .zip(s.char_indices())
+                .enumerate()
+                .map(|(i, (&t, (position, c)))| {
+                    Gene::from_code(t, c).ok_or_else(|| {
+                        let codes: String = Gene::all(t).iter().map(|g| g.to_code()).collect();
+                        error(
+                            position,
+                            format!(
+                                "gene {} is {:?}, so it's one of \"{}\" in shorthand, not '{}'",
+                                i + 1,
+                                t,
+                                codes,
+                                c
+                            ),
+                        )
+                    })
+                })
+                .collect();
+        }
+
+        let mut position = 0;
+        let mut genes = vec![];
+        for (i, (&t, &c)) in gene_print.iter().zip(letters.iter()).enumerate() {
+            let rest = &s[position..];
+            let Some((gene, len)) = Gene::parse_prefix(t, c, rest) else {
+                let example = Gene::all(t)[0].to_string(c);
+                let found: String = rest.chars().take(example.len()).collect();
+                let reason = if found.is_empty() {
+                    format!(
+                        "it ends before gene {}, written like \"{}\"",
+                        i + 1,
+                        example
+                    )
+                } else {
+                    format!(
+                        "gene {} is written like \"{}\", not \"{}\"",
+                        i + 1,
+                        example,
+                        found
+                    )
+                };
+                return Err(error(position, reason));
+            };
+            genes.push(gene);
+            position += len;
+        }
+        if position < s.len() {
+            return Err(error(
+                position,
+                format!("there are only {} genes", gene_print.len()),
+            ));
+        }
+        Ok(Genotype { genes })
+    }
+
+    /// The code of every gene one after the other, like "2001" for a red rose seed
+    pub fn to_code(&self) -> String {
+        self.genes.iter().map(|g| g.to_code()).collect()
+    }
+
+    /// None if the code doesn't have a valid character for every gene in the gene print
+    pub fn from_code(gene_print: &[GeneType], code: &str) -> Option<Self> {
+        if code.chars().count() != gene_print.len() {
+            return None;
+        }
+        gene_print
+            .iter()
+            .zip(code.chars())
+            .map(|(&t, c)| Gene::from_code(t, c))
             .collect()
     }
 
@@ -370,3 +896,253 @@ impl Genotype {
         self.genes.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::{ACNHFlowerType, FlowerContext, FlowerType};
+
+    const M0: Gene = Gene::Mendelian(MendelianGene::HomozygousRecessive);
+    const M1: Gene = Gene::Mendelian(MendelianGene::Heterozygous);
+    const M2: Gene = Gene::Mendelian(MendelianGene::HomozygousDominant);
+
+    /// The chance of an outcome, 0 if it can't happen
+    fn chance<T: PartialEq>(distribution: &Distribution<T>, outcome: &T) -> f64 {
+        distribution
+            .iter()
+            .find(|(o, _)| o == outcome)
+            .map_or(0.0, |(_, p)| *p)
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn gene_type_matches_the_gene() {
+        for gene_type in [
+            GeneType::Mendelian,
+            GeneType::Bloodlike,
+            GeneType::Quadruplet,
+        ] {
+            for gene in Gene::all(gene_type) {
+                assert_eq!(gene.gene_type(), gene_type);
+            }
+        }
+    }
+
+    #[test]
+    fn mendelian_cross() {
+        let children =
+            MendelianGene::Heterozygous.offspring_distribution(&MendelianGene::Heterozygous);
+        assert_close(chance(&children, &MendelianGene::HomozygousDominant), 0.25);
+        assert_close(chance(&children, &MendelianGene::Heterozygous), 0.5);
+        assert_close(chance(&children, &MendelianGene::HomozygousRecessive), 0.25);
+    }
+
+    #[test]
+    fn bloodlike_crosses() {
+        use BloodlikeGene::*;
+        let children = BloodHeterozygousB.offspring_distribution(&BloodHeterozygousB);
+        assert_close(chance(&children, &BloodHomozygousB), 0.25);
+        assert_close(chance(&children, &BloodHeterozygousB), 0.5);
+        assert_close(chance(&children, &BloodO), 0.25);
+
+        // both orders of an A and a B allele are AB
+        let children = BloodAB.offspring_distribution(&BloodAB);
+        assert_close(chance(&children, &BloodHomozygousA), 0.25);
+        assert_close(chance(&children, &BloodAB), 0.5);
+        assert_close(chance(&children, &BloodHomozygousB), 0.25);
+
+        let children = BloodHeterozygousA.offspring_distribution(&BloodHeterozygousB);
+        for gene in [BloodAB, BloodHeterozygousA, BloodHeterozygousB, BloodO] {
+            assert_close(chance(&children, &gene), 0.25);
+        }
+    }
+
+    #[test]
+    fn quadruplet_alleles() {
+        for gene in QuadrupletGene::ALL {
+            assert_eq!(QuadrupletGene::from_alleles(gene.to_alleles()), gene);
+        }
+        assert_eq!(
+            QuadrupletGene::from_alleles([true; 4]),
+            QuadrupletGene::HomozygousDominant
+        );
+        assert_eq!(
+            QuadrupletGene::from_alleles([false; 4]),
+            QuadrupletGene::HomozygousRecessive
+        );
+        assert_eq!(
+            QuadrupletGene::HomozygousDominant.flipped(),
+            QuadrupletGene::HomozygousRecessive
+        );
+    }
+
+    #[test]
+    fn quadruplet_cross() {
+        use QuadrupletGene::*;
+        // AAaa passes on AA 1/6, Aa 4/6 and aa 1/6 of the time
+        let children = Heterozygous.offspring_distribution(&Heterozygous);
+        assert_close(chance(&children, &HomozygousRecessive), 1.0 / 36.0);
+        assert_close(chance(&children, &SemihomozygousRecessive), 8.0 / 36.0);
+        assert_close(chance(&children, &Heterozygous), 18.0 / 36.0);
+        assert_close(chance(&children, &SemihomozygousDominant), 8.0 / 36.0);
+        assert_close(chance(&children, &HomozygousDominant), 1.0 / 36.0);
+    }
+
+    #[test]
+    fn distributions_add_up_to_one() {
+        for gene_type in [
+            GeneType::Mendelian,
+            GeneType::Bloodlike,
+            GeneType::Quadruplet,
+        ] {
+            for a in Gene::all(gene_type) {
+                for b in Gene::all(gene_type) {
+                    let children = a.offspring_distribution(&b).unwrap();
+                    assert_close(children.iter().map(|(_, p)| p).sum(), 1.0);
+                }
+            }
+        }
+        let mendelian = Gene::Mendelian(MendelianGene::Heterozygous);
+        let bloodlike = Gene::Bloodlike(BloodlikeGene::BloodO);
+        assert_eq!(mendelian.offspring_distribution(&bloodlike), None);
+    }
+
+    #[test]
+    fn red_rose_seeds_make_black_roses() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let rose = FlowerType::ACNH(ACNHFlowerType::Rose);
+        let red = Genotype::new(vec![M2, M0, M0, M1]);
+        let children = red.offspring_distribution(&red).unwrap();
+        assert_eq!(children.len(), 3);
+        assert_close(
+            chance(&children, &Genotype::new(vec![M2, M0, M0, M2])),
+            0.25,
+        );
+        assert_close(chance(&children, &red), 0.5);
+        assert_close(
+            chance(&children, &Genotype::new(vec![M2, M0, M0, M0])),
+            0.25,
+        );
+
+        let colors = flower_context
+            .phenotype_distribution(rose, &children)
+            .unwrap();
+        assert_close(chance(&colors, &"Pink".to_string()), 0.25);
+        assert_close(chance(&colors, &"Red".to_string()), 0.5);
+        assert_close(chance(&colors, &"Black".to_string()), 0.25);
+    }
+
+    #[test]
+    fn yellow_and_white_rose_seeds() {
+        let rose = FlowerType::ACNH(ACNHFlowerType::Rose);
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let yellow = Genotype::new(vec![M0, M2, M0, M0]);
+        let white = Genotype::new(vec![M0, M0, M1, M0]);
+        let children = yellow.offspring_distribution(&white).unwrap();
+        let colors = flower_context
+            .phenotype_distribution(rose, &children)
+            .unwrap();
+        // half the children get a white allele from the white seed
+        assert_close(chance(&colors, &"Yellow".to_string()), 0.5);
+        assert_close(chance(&colors, &"White".to_string()), 0.5);
+    }
+
+    #[test]
+    fn genotypes_read_back_what_they_write() {
+        let gene_prints = [
+            vec![GeneType::Mendelian; 4],
+            vec![GeneType::Bloodlike, GeneType::Mendelian],
+            vec![
+                GeneType::Quadruplet,
+                GeneType::Bloodlike,
+                GeneType::Mendelian,
+            ],
+        ];
+        let letters = ['R', 'y', 'W'];
+        for gene_print in gene_prints {
+            for genotype in Genotype::all(&gene_print) {
+                assert_eq!(
+                    genotype.to_string().parse::<Genotype>(),
+                    Ok(genotype.clone())
+                );
+                let letters = &letters.repeat(2)[..gene_print.len()];
+                let code = genotype.to_code();
+                assert_eq!(
+                    Genotype::parse_with(&gene_print, letters, &code),
+                    Ok(genotype.clone())
+                );
+                let alleles = genotype.to_allele_string(letters);
+                assert_eq!(
+                    Genotype::parse_with(&gene_print, letters, &alleles),
+                    Ok(genotype)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_genotypes_are_read_gene_by_gene() {
+        let genotype: Genotype = "AaIaIbCCcc".parse().unwrap();
+        assert_eq!(
+            genotype.gene_print(),
+            vec![
+                GeneType::Mendelian,
+                GeneType::Bloodlike,
+                GeneType::Quadruplet
+            ]
+        );
+        assert_eq!(genotype.to_string(), "AaIaIbCCcc");
+        assert_eq!("2001".parse::<Genotype>().unwrap().to_code(), "2001");
+    }
+
+    #[test]
+    fn bad_characters_are_pointed_at() {
+        let error = "20a1".parse::<Genotype>().unwrap_err();
+        assert_eq!(error.position(), 2);
+        assert!(error.to_string().contains("at character 3"), "{}", error);
+
+        let rose = [GeneType::Mendelian; 4];
+        let letters = ['R', 'Y', 'w', 'S'];
+        assert_eq!(
+            Genotype::parse_with(&rose, &letters, "20a1")
+                .unwrap_err()
+                .position(),
+            2
+        );
+        assert_eq!(
+            Genotype::parse_with(&rose, &letters, "200")
+                .unwrap_err()
+                .position(),
+            3
+        );
+        assert_eq!(
+            Genotype::parse_with(&rose, &letters, "20011")
+                .unwrap_err()
+                .position(),
+            4
+        );
+    }
+
+    #[test]
+    fn non_ascii_text_is_an_error() {
+        assert!("アア".parse::<Genotype>().is_err());
+        assert!("Aaアア".parse::<Genotype>().is_err());
+        let error = "ア2".parse::<Genotype>().unwrap_err();
+        assert_eq!(error.position(), 0);
+        assert!(error.to_string().contains("at character 1"), "{}", error);
+
+        let hyacinth = [GeneType::Mendelian; 3];
+        let letters = ['R', 'Y', 'w'];
+        assert!(Genotype::parse_with(&hyacinth, &letters, "RRアアww").is_err());
+        assert!(Genotype::parse_with(&hyacinth, &letters, "RRyywwé").is_err());
+        let error = Genotype::parse_with(&hyacinth, &letters, "éé1").unwrap_err();
+        assert!(error.to_string().contains("at character 1"), "{}", error);
+        // only ASCII letters can be gene letters
+        assert!(Gene::parse_prefix(GeneType::Mendelian, 'ä', "ää").is_none());
+        assert!(Gene::parse_prefix(GeneType::Bloodlike, 'ä', "ääb").is_none());
+        assert!(Genotype::parse_with(&[GeneType::Mendelian], &['ä'], "ÄÄ").is_err());
+    }
+}
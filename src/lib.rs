@@ -0,0 +1,32 @@
+#![allow(
+    clippy::upper_case_acronyms,
+    clippy::wrong_self_convention,
+    clippy::inherent_to_string
+)]
+
+//! Flower genetics from Animal Crossing: New Horizons (and any made up flowers), and the garden game built on them.
+//!
+//! `genetics`, `flowers` and the terminal styling re-exported here are meant to be used from other code:
+//! ```
+//! use blute::flowers::flower::{ACNHFlowerType, FlowerContext, FlowerType};
+//! use blute::genetics::Genotype;
+//!
+//! let flower_context = FlowerContext::acnh_only().unwrap();
+//! let rose = FlowerType::ACNH(ACNHFlowerType::Rose);
+//! let gene_print = flower_context.gene_print(rose).unwrap();
+//! let red = Genotype::from_code(&gene_print, "2001").unwrap();
+//! let white = Genotype::from_code(&gene_print, "0010").unwrap();
+//!
+//! let children = red.offspring_distribution(&white).unwrap();
+//! let colors = flower_context.phenotype_distribution(rose, &children).unwrap();
+//! assert!(colors.iter().any(|(color, _)| color == "Pink"));
+//! ```
+//! `context`, `ui` and `save` are the pieces of the game itself and can change at any time.
+
+pub mod context;
+pub mod flowers;
+pub mod genetics;
+pub mod save;
+pub mod ui;
+
+pub use ui::terminal::{AnsiColor, AnsiEffect, TextBlueprint};
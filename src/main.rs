@@ -1,31 +1,101 @@
-#![allow(unused)]
+#![allow(
+    clippy::upper_case_acronyms,
+    clippy::wrong_self_convention,
+    clippy::inherent_to_string
+)]
 
-mod context;
-mod flowers;
-mod genetics;
-mod ui;
+mod cli;
 
-use std::rc::Rc;
+use std::time::Duration;
 
-use context::Context;
-use flowers::{
-    acnh_flowers::{ACNHMum, ACNHRose},
-    flower::{Flower, FlowerContext},
-};
-use genetics::{Gene, Genotype, MendelianGene};
-use getch_rs::Key;
-use ui::board::Board;
-use ui::terminal::{AnsiColor, AnsiEffect, KeyStack, TextBlueprint, new_keystack};
+use blute::context::{self, Context};
+use blute::ui::session::TerminalSession;
 
 fn main() {
-    let mut context = Context::new();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = cli::run(&args) {
+        if let Err(e) = result {
+            eprintln!("blute: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    // clear terminal
-    print!("\x1Bc");
+    let mut args = args.into_iter();
+    let mut seed: Option<u64> = None;
+    let mut load_path: Option<String> = None;
+    let mut tick_rate = context::DEFAULT_TICK_RATE;
+    let mut board_size: Option<(usize, usize)> = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                seed = Some(
+                    args.next()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or_else(|| usage_error("--seed needs a number")),
+                )
+            }
+            "--load" => {
+                load_path = Some(
+                    args.next()
+                        .unwrap_or_else(|| usage_error("--load needs a path")),
+                )
+            }
+            "--board" => {
+                board_size = Some(
+                    args.next()
+                        .and_then(|s| {
+                            let (width, height) = s.split_once('x')?;
+                            Some((width.parse().ok()?, height.parse().ok()?))
+                        })
+                        .filter(|&(width, height)| width > 0 && height > 0)
+                        .unwrap_or_else(|| usage_error("--board needs a size like 64x64")),
+                )
+            }
+            "--tick-rate" => {
+                tick_rate = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|&ms| ms > 0)
+                    .map(Duration::from_millis)
+                    .unwrap_or_else(|| usage_error("--tick-rate needs a number of milliseconds"))
+            }
+            _ => usage_error(&format!("unknown argument: {}", arg)),
+        }
+    }
 
-    loop {
-        if context.update() {
-            break;
+    // put back when it's dropped at the end of main, or on a panic or signal
+    let session = TerminalSession::start().expect("blute needs to be run in a terminal");
+    let mut context = match Context::with_tick_rate(seed.unwrap_or_else(rand::random), tick_rate) {
+        Ok(context) => context,
+        Err(e) => {
+            drop(session);
+            eprintln!("blute: {}", e);
+            std::process::exit(1);
         }
+    };
+    if let Some(size) = board_size {
+        context
+            .set_board_size(size)
+            .expect("the board size was checked with the arguments");
+    }
+    if let Some(path) = load_path
+        && let Err(e) = context.load(std::path::Path::new(&path))
+    {
+        // exiting skips drops, so the terminal has to be put back first
+        drop(session);
+        usage_error(&format!("couldn't load {}: {}", path, e));
     }
+
+    if let Err(e) = context.run() {
+        drop(session);
+        eprintln!("blute: couldn't draw the game: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Says what's wrong with the arguments and how blute is used, then exits like the subcommands do
+fn usage_error(message: &str) -> ! {
+    eprintln!("blute: {}\n{}", message, cli::USAGE);
+    std::process::exit(1);
 }
@@ -0,0 +1,232 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, from_reader, from_value, to_writer_pretty};
+
+use crate::flowers::flower::{ACNHFlowerType, AnyFlower, FlowerContext, FlowerType};
+use crate::flowers::lineage::{Lineage, Pedigree};
+use crate::genetics::Genotype;
+use crate::ui::Grid;
+
+/// Bumped whenever the save format changes so old saves aren't misread
+pub const SAVE_VERSION: u32 = 1;
+
+/// Where the game gets saved to if no other path was given
+pub const DEFAULT_SAVE_PATH: &str = "blute_save.json";
+
+/// A species as it is saved. Custom species go by name,
+/// since their place among the loaded species changes when species files are added or removed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SavedFlowerType {
+    ACNH(ACNHFlowerType),
+    Custom(String),
+}
+
+/// Everything needed to bring a flower back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFlower {
+    pub flower_type: SavedFlowerType,
+    pub genotype: Genotype,
+}
+
+impl SavedFlower {
+    pub fn from_flower(flower: &AnyFlower) -> Self {
+        let info = flower.info();
+        Self {
+            flower_type: match info.flower_type() {
+                FlowerType::ACNH(t) => SavedFlowerType::ACNH(t),
+                FlowerType::Custom(_) => SavedFlowerType::Custom(info.name()),
+            },
+            genotype: flower.genotype(),
+        }
+    }
+
+    /// Errors if the species isn't known, like a custom species that's gone missing,
+    /// or if the genotype doesn't have the genes of the species
+    pub fn to_flower(&self, flower_context: &FlowerContext) -> Result<AnyFlower, String> {
+        let flower_type = match &self.flower_type {
+            SavedFlowerType::ACNH(t) => FlowerType::ACNH(*t),
+            SavedFlowerType::Custom(name) => flower_context
+                .custom_flower_type(name)
+                .ok_or_else(|| format!("there's no species named \"{}\"", name))?,
+        };
+        let gene_print = flower_context
+            .gene_print(flower_type)
+            .ok_or_else(|| format!("there's no species {:?}", self.flower_type))?;
+        if self.genotype.gene_print() != gene_print {
+            return Err(format!(
+                "{:?} has genes {:?}, but the species has {:?}",
+                self.flower_type,
+                self.genotype.gene_print(),
+                gene_print
+            ));
+        }
+        flower_context
+            .new_flower(flower_type, self.genotype.clone())
+            .ok_or_else(|| format!("there's no species {:?}", self.flower_type))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedBoard {
+    pub flowers: Grid<Option<SavedFlower>>,
+    pub watered: Grid<bool>,
+    pub lineages: Grid<Option<Lineage>>,
+    pub cursor_pos: (usize, usize),
+    /// The flower being carried around, if any
+    pub held: Option<(SavedFlower, Option<Lineage>)>,
+}
+
+/// An inventory slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SavedItem {
+    Seeds {
+        flower: SavedFlower,
+        count: usize,
+    },
+    Picked {
+        flower: SavedFlower,
+        lineage: Option<Lineage>,
+    },
+}
+
+/// The whole game state as it is written to a save file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+    pub seed: u64,
+    pub day: u32,
+    pub board: SavedBoard,
+    pub shop_cursor: (usize, usize),
+    pub inventory: Vec<SavedItem>,
+    pub bells: u32,
+    pub area_watering: bool,
+    pub pedigree: Pedigree,
+}
+
+impl SaveData {
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let writer = BufWriter::new(File::create(path)?);
+        to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Self::from_json(from_reader(reader)?)
+    }
+
+    /// The version is checked before anything else, since other versions have other fields
+    fn from_json(json: Value) -> Result<Self, Box<dyn Error>> {
+        let version = json
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or("Save file has no version")?;
+        if version != SAVE_VERSION as u64 {
+            return Err(format!(
+                "Save file version {} is not supported (expected {})",
+                version, SAVE_VERSION
+            )
+            .into());
+        }
+        Ok(from_value(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genetics::GeneType;
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A species directory with a one gene species for each name, in a fresh temporary directory
+    fn species_dir(test: &str, names: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blute-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in names {
+            let species = json!({
+                "name": name,
+                "glyph": "*",
+                "gene_print": ["Mendelian"],
+                "phenotypes": { "0": "White", "1": "Red", "2": "Red" },
+                "seeds": ["0", "2"],
+            });
+            fs::write(
+                dir.join(format!("{}.json", name.to_lowercase())),
+                species.to_string(),
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    fn saved(flower_type: ACNHFlowerType, gene_print: &[GeneType], code: &str) -> SavedFlower {
+        SavedFlower {
+            flower_type: SavedFlowerType::ACNH(flower_type),
+            genotype: Genotype::from_code(gene_print, code).unwrap(),
+        }
+    }
+
+    #[test]
+    fn flowers_come_back_with_their_genes() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let rose = saved(ACNHFlowerType::Rose, &[GeneType::Mendelian; 4], "2001");
+        let flower = rose.to_flower(&flower_context).unwrap();
+        assert_eq!(flower.genotype(), rose.genotype);
+    }
+
+    #[test]
+    fn genotypes_of_another_species_are_rejected() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        // a rose has four genes, not three
+        let rose = saved(ACNHFlowerType::Rose, &[GeneType::Mendelian; 3], "200");
+        assert!(rose.to_flower(&flower_context).is_err());
+        let lily_of_the_valley =
+            saved(ACNHFlowerType::LilyOfTheValley, &[GeneType::Bloodlike], "X");
+        assert!(lily_of_the_valley.to_flower(&flower_context).is_err());
+        assert!(
+            flower_context
+                .new_flower(
+                    FlowerType::ACNH(ACNHFlowerType::Rose),
+                    rose.genotype.clone()
+                )
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn custom_species_are_found_by_name_after_others_are_added() {
+        let dir = species_dir("by-name", &["Moss"]);
+        let before = FlowerContext::with_species_dir(&dir).unwrap();
+        let moss = before.custom_flower_type("Moss").unwrap();
+        let saved = SavedFlower::from_flower(&before.seeds_of(moss)[1]);
+        assert_eq!(saved.flower_type, SavedFlowerType::Custom("Moss".into()));
+
+        // a species sorted before moss moves it to another place
+        let after =
+            FlowerContext::with_species_dir(&species_dir("by-name", &["Fern", "Moss"])).unwrap();
+        let flower = saved.to_flower(&after).unwrap();
+        assert_eq!(flower.info().name(), "Moss");
+        assert_ne!(flower.info().flower_type(), moss);
+        assert!(
+            saved
+                .to_flower(&FlowerContext::acnh_only().unwrap())
+                .is_err()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn other_versions_are_rejected_before_their_fields_are_read() {
+        let json = serde_json::json!({ "version": SAVE_VERSION + 1, "board": "something new" });
+        let e = SaveData::from_json(json).unwrap_err();
+        assert!(e.to_string().contains("not supported"), "{}", e);
+        assert!(SaveData::from_json(json!({ "seed": 1 })).is_err());
+    }
+}
@@ -1,8 +1,18 @@
+pub mod backend;
 pub mod board;
 pub mod display;
+pub mod family_tree;
+pub mod inspector;
+pub mod inventory;
+pub mod layout;
+pub mod menu;
+pub mod session;
 pub mod shop;
+pub mod stats;
 pub mod terminal;
 
+use terminal::TextBlueprint;
+
 pub type Grid<T> = Vec<Vec<T>>;
 
 pub fn to_grid(s: String, row_length: usize) -> Grid<String> {
@@ -16,3 +26,15 @@ pub fn to_grid(s: String, row_length: usize) -> Grid<String> {
 pub fn empty_string_grid(size: (usize, usize)) -> Grid<String> {
     vec![vec![" ".to_string(); size.0]; size.1]
 }
+
+/// A row of `width` cells holding the text, cut off or padded with spaces
+pub fn text_row(text: &str, width: usize, blueprint: Option<&TextBlueprint>) -> Vec<String> {
+    text.chars()
+        .chain(std::iter::repeat(' '))
+        .take(width)
+        .map(|c| match blueprint {
+            Some(b) if c != ' ' => b.apply(c.to_string().as_str()),
+            _ => c.to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,372 @@
+use std::io::{self, StdoutLock, Write};
+
+use super::{Grid, terminal::TextBlueprint};
+
+/// Where finished frames end up. The game draws to the terminal, and tests draw to memory
+/// so what the player would see can be compared against a snapshot
+pub trait Backend {
+    /// Draws a frame of cells, each a character that might be wrapped in ANSI styling,
+    /// with the status line under it
+    fn draw(&mut self, frame: &Grid<String>, status: &str) -> io::Result<()>;
+
+    /// Wipes whatever was drawn before, like when the layout gets smaller
+    fn clear(&mut self) -> io::Result<()>;
+}
+
+/// Unchanged cells between two changed ones that get redrawn anyway,
+/// since jumping the cursor over them takes about as many bytes
+const MAX_REDRAWN_GAP: usize = 6;
+
+/// The SGR parameters and the visible text of every cell, worked out like the terminal would:
+/// a style lasts until it's reset, even past the cell it started in
+fn resolve_cells(frame: &Grid<String>) -> Grid<(String, String)> {
+    let mut style = String::new();
+    frame
+        .iter()
+        .map(|cells| {
+            cells
+                .iter()
+                .map(|cell| {
+                    let mut text = String::new();
+                    let mut cell_style = None;
+                    let mut chars = cell.chars().peekable();
+                    while let Some(c) = chars.next() {
+                        if c == '\x1B' && chars.peek() == Some(&'[') {
+                            chars.next();
+                            let params: String = chars.by_ref().take_while(|&c| c != 'm').collect();
+                            match params.as_str() {
+                                "" | "0" => style.clear(),
+                                _ if style.is_empty() => style = params,
+                                _ => style = format!("{};{}", style, params),
+                            }
+                            continue;
+                        }
+                        // the style the cell is drawn in is the one its text starts with
+                        cell_style.get_or_insert_with(|| style.clone());
+                        text.push(c);
+                    }
+                    (cell_style.unwrap_or_else(|| style.clone()), text)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Draws to the terminal, only rewriting the cells that changed since the last frame
+pub struct TerminalBackend {
+    lock: StdoutLock<'static>,
+    /// What's on the screen now, None if it has to be drawn from scratch
+    front: Option<Grid<(String, String)>>,
+    status: String,
+}
+
+impl Default for TerminalBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerminalBackend {
+    pub fn new() -> TerminalBackend {
+        TerminalBackend {
+            lock: std::io::stdout().lock(),
+            front: None,
+            status: String::new(),
+        }
+    }
+
+    /// The escapes that turn the front frame into the back one
+    fn diff(front: Option<&Grid<(String, String)>>, back: &Grid<(String, String)>) -> String {
+        let mut out = String::new();
+        let front = match front {
+            Some(front) if front.len() == back.len() => front,
+            // a frame with a different height can't be patched, so start over
+            _ => {
+                out.push_str("\x1B[2J");
+                &vec![]
+            }
+        };
+
+        for (y, row) in back.iter().enumerate() {
+            let old_row = front.get(y);
+            let changed: Vec<usize> = (0..row.len())
+                .filter(|&x| old_row.and_then(|r| r.get(x)) != Some(&row[x]))
+                .collect();
+
+            // runs of changed cells, with small gaps between them drawn over too
+            let mut runs: Vec<(usize, usize)> = vec![];
+            for x in changed {
+                match runs.last_mut() {
+                    Some((_, end)) if x - *end <= MAX_REDRAWN_GAP => *end = x + 1,
+                    _ => runs.push((x, x + 1)),
+                }
+            }
+
+            for (start, end) in runs {
+                // every cell is one column wide
+                out.push_str(&format!("\x1B[{};{}H", y + 1, start + 1));
+                let mut style = "";
+                for (cell_style, text) in &row[start..end] {
+                    if cell_style != style {
+                        if !style.is_empty() {
+                            out.push_str(&TextBlueprint::reset());
+                        }
+                        if !cell_style.is_empty() {
+                            out.push_str(&format!("\x1B[{}m", cell_style));
+                        }
+                        style = cell_style;
+                    }
+                    out.push_str(text);
+                }
+                if !style.is_empty() {
+                    out.push_str(&TextBlueprint::reset());
+                }
+            }
+            // the old row went further
+            if old_row.is_some_and(|r| r.len() > row.len()) {
+                out.push_str(&format!("\x1B[{};{}H\x1B[K", y + 1, row.len() + 1));
+            }
+        }
+        out
+    }
+}
+
+impl Backend for TerminalBackend {
+    fn draw(&mut self, frame: &Grid<String>, status: &str) -> io::Result<()> {
+        let back = resolve_cells(frame);
+        let mut out = Self::diff(self.front.as_ref(), &back);
+        if self.front.is_none() || out.starts_with("\x1B[2J") || status != self.status {
+            out.push_str(&format!("\x1B[{}H\x1B[K{}", back.len() + 1, status));
+            self.status = status.to_string();
+        }
+        self.front = Some(back);
+
+        // one write so the terminal never shows half a frame
+        self.lock.write_all(out.as_bytes())?;
+        self.lock.flush()
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.front = None;
+        write!(self.lock, "\x1B[2J")
+    }
+}
+
+/// A run of cells on one line drawn with the same style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleSpan {
+    pub row: usize,
+    /// The first cell of the run
+    pub start: usize,
+    /// One past the last cell of the run
+    pub end: usize,
+    /// The SGR parameters the cells were drawn with, like "38;5;221" or "48;5;236;1"
+    pub style: String,
+}
+
+/// A drawn frame as plain text, with the styling pulled out into spans
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    lines: Vec<String>,
+    spans: Vec<StyleSpan>,
+    status: String,
+}
+
+impl Frame {
+    pub fn from_grid(grid: &Grid<String>, status: &str) -> Frame {
+        let mut lines = vec![];
+        let mut spans: Vec<StyleSpan> = vec![];
+        for (row, cells) in resolve_cells(grid).into_iter().enumerate() {
+            let mut line = String::new();
+            let mut column = 0;
+            for (style, text) in cells {
+                for c in text.chars() {
+                    line.push(c);
+                    if !style.is_empty() {
+                        match spans.last_mut() {
+                            Some(span)
+                                if span.row == row && span.end == column && span.style == style =>
+                            {
+                                span.end += 1
+                            }
+                            _ => spans.push(StyleSpan {
+                                row,
+                                start: column,
+                                end: column + 1,
+                                style: style.clone(),
+                            }),
+                        }
+                    }
+                    column += 1;
+                }
+            }
+            lines.push(line);
+        }
+        Frame {
+            lines,
+            spans,
+            status: status.to_string(),
+        }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn spans(&self) -> &[StyleSpan] {
+        &self.spans
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// The styles over a cell, empty if it's plain
+    pub fn style_at(&self, pos: (usize, usize)) -> &str {
+        self.spans
+            .iter()
+            .find(|s| s.row == pos.1 && (s.start..s.end).contains(&pos.0))
+            .map_or("", |s| s.style.as_str())
+    }
+
+    /// The frame as text to compare against a golden file: the lines and the status,
+    /// then a `--` line and each span as "row start..end style"
+    pub fn to_snapshot(&self) -> String {
+        let mut snapshot = String::new();
+        for line in self.lines.iter().chain([&self.status]) {
+            snapshot.push_str(line.trim_end());
+            snapshot.push('\n');
+        }
+        snapshot.push_str("--\n");
+        for span in self.spans.iter() {
+            snapshot.push_str(&format!(
+                "{} {}..{} {}\n",
+                span.row, span.start, span.end, span.style
+            ));
+        }
+        snapshot
+    }
+}
+
+/// Keeps every frame drawn instead of showing it
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    frames: Vec<Frame>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> MemoryBackend {
+        Self::default()
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn last_frame(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn draw(&mut self, frame: &Grid<String>, status: &str) -> io::Result<()> {
+        self.frames.push(Frame::from_grid(frame, status));
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame of plain cells, one per character
+    fn plain(lines: &[&str]) -> Grid<String> {
+        lines
+            .iter()
+            .map(|line| line.chars().map(|c| c.to_string()).collect())
+            .collect()
+    }
+
+    fn diff(front: &Grid<String>, back: &Grid<String>) -> String {
+        TerminalBackend::diff(Some(&resolve_cells(front)), &resolve_cells(back))
+    }
+
+    #[test]
+    fn styles_last_until_they_are_reset() {
+        let frame = vec![vec![
+            "\x1B[38;5;160mR".to_string(),
+            "x".to_string(),
+            "\x1B[0my".to_string(),
+        ]];
+        let cells = resolve_cells(&frame);
+        assert_eq!(cells[0][0], ("38;5;160".to_string(), "R".to_string()));
+        assert_eq!(cells[0][1], ("38;5;160".to_string(), "x".to_string()));
+        assert_eq!(cells[0][2], (String::new(), "y".to_string()));
+    }
+
+    #[test]
+    fn an_unchanged_frame_draws_nothing() {
+        let frame = plain(&["abc", "def"]);
+        assert_eq!(diff(&frame, &frame), "");
+    }
+
+    #[test]
+    fn one_changed_cell_is_one_move_and_the_cell() {
+        assert_eq!(
+            diff(&plain(&["abc", "def"]), &plain(&["abc", "dXf"])),
+            "\x1B[2;2HX"
+        );
+    }
+
+    #[test]
+    fn cells_of_the_same_style_are_drawn_under_one_escape() {
+        let red = TextBlueprint::new()
+            .with_text_color(crate::ui::terminal::AnsiColor::from_code(160))
+            .to_owned();
+        let mut back = plain(&["abcd"]);
+        back[0][1] = red.apply("X");
+        back[0][2] = red.apply("Y");
+        assert_eq!(
+            diff(&plain(&["abcd"]), &back),
+            "\x1B[1;2H\x1B[38;5;160mXY\x1B[0m"
+        );
+    }
+
+    #[test]
+    fn small_gaps_are_drawn_over_and_big_ones_jumped() {
+        let front = plain(&["....................."]);
+        // MAX_REDRAWN_GAP unchanged cells are still drawn over
+        let near = format!("X{}X", ".".repeat(MAX_REDRAWN_GAP));
+        let near = format!("{}{}", near, ".".repeat(21 - near.len()));
+        assert_eq!(
+            diff(&front, &plain(&[&near])),
+            format!("\x1B[1;1H{}", &near[..MAX_REDRAWN_GAP + 2])
+        );
+        // one more and it's cheaper to jump
+        let far = format!("X{}X", ".".repeat(MAX_REDRAWN_GAP + 1));
+        let far = format!("{}{}", far, ".".repeat(21 - far.len()));
+        assert_eq!(
+            diff(&front, &plain(&[&far])),
+            format!("\x1B[1;1HX\x1B[1;{}HX", MAX_REDRAWN_GAP + 3)
+        );
+    }
+
+    #[test]
+    fn a_new_height_clears_the_screen() {
+        let out = diff(&plain(&["ab"]), &plain(&["ab", "cd"]));
+        assert!(out.starts_with("\x1B[2J"));
+        assert!(out.contains("\x1B[1;1Hab") && out.contains("\x1B[2;1Hcd"));
+        assert!(
+            TerminalBackend::diff(None, &resolve_cells(&plain(&["ab"]))).starts_with("\x1B[2J")
+        );
+    }
+
+    #[test]
+    fn a_shorter_row_has_its_end_erased() {
+        assert_eq!(diff(&plain(&["abcd"]), &plain(&["ab"])), "\x1B[1;3H\x1B[K");
+    }
+}
@@ -1,20 +1,60 @@
-use std::{ops::Deref, rc::Rc};
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
 
 use super::Grid;
-use crate::context::Context;
+use crate::save::{SavedBoard, SavedFlower};
 use crate::{
     flowers::{
-        acnh_flowers::{ACNHMum, ACNHRose},
-        flower::{AnyFlower, Flower, FlowerContext, FlowerData, FlowerType},
+        flower::{AnyFlower, FlowerContext},
+        lineage::{Lineage, Pedigree},
     },
-    genetics::Genotype,
     ui::terminal::{AnsiColor, TextBlueprint},
 };
 
+/// How many cells across and down a new board is
+pub const DEFAULT_BOARD_SIZE: (usize, usize) = (9, 9);
+
+/// The chance that a flower breeds on a given day when it has a partner and room for a child
+pub const BREED_CHANCE: f64 = 0.1;
+/// How much more likely a watered flower is to breed
+pub const WATERED_BREED_MULTIPLIER: f64 = 5.0;
+
+/// A flower breeding with one of its neighbors during a day
+#[derive(Debug, Clone)]
+pub struct BreedEvent {
+    parents: ((usize, usize), (usize, usize)),
+    child_pos: (usize, usize),
+    child: AnyFlower,
+}
+
+impl BreedEvent {
+    pub fn parents(&self) -> ((usize, usize), (usize, usize)) {
+        self.parents
+    }
+
+    pub fn child_pos(&self) -> (usize, usize) {
+        self.child_pos
+    }
+
+    pub fn child(&self) -> AnyFlower {
+        self.child.clone()
+    }
+}
+
 pub struct Board {
     board: Grid<Option<AnyFlower>>,
     board_size: (usize, usize),
     cursor_pos: (usize, usize),
+    /// Which cells have been watered today
+    watered: Grid<bool>,
+    /// Where the flower in each cell came from, if it was recorded
+    lineages: Grid<Option<Lineage>>,
+    /// A flower picked up off the board, keeping its genotype and lineage until it's put down again
+    held: Option<(AnyFlower, Option<Lineage>)>,
+    /// The bottom left cell shown, for boards bigger than the screen
+    view_pos: (usize, usize),
+    /// How many cells across and down were shown last frame
+    view_size: (usize, usize),
 }
 
 impl Board {
@@ -24,6 +64,39 @@ impl Board {
             .to_owned()
     }
 
+    pub fn watered_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_background_color(AnsiColor::from_code(17))
+            .to_owned()
+    }
+
+    /// The cursor while a flower is being carried
+    pub fn holding_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_background_color(AnsiColor::from_code(94))
+            .to_owned()
+    }
+
+    /// The part of the minimap that's on screen
+    pub fn view_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_background_color(AnsiColor::from_code(238))
+            .to_owned()
+    }
+
+    /// Where the cursor is on the minimap
+    pub fn minimap_cursor_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_background_color(AnsiColor::from_code(61))
+            .to_owned()
+    }
+
+    pub fn selected_watered_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_background_color(AnsiColor::from_code(24))
+            .to_owned()
+    }
+
     pub fn new(size: (usize, usize)) -> Option<Board> {
         if size.0 == 0 || size.1 == 0 {
             return None;
@@ -31,15 +104,24 @@ impl Board {
 
         let (width, height) = size;
 
-        let empty_vec: Vec<Option<AnyFlower>> = vec![None; width as usize];
+        let empty_vec: Vec<Option<AnyFlower>> = vec![None; width];
 
         Some(Board {
-            board: vec![empty_vec; height as usize],
+            board: vec![empty_vec; height],
             board_size: size,
             cursor_pos: (0, 0),
+            watered: vec![vec![false; width]; height],
+            lineages: vec![vec![None; width]; height],
+            held: None,
+            view_pos: (0, 0),
+            view_size: size,
         })
     }
 
+    pub fn size(&self) -> (usize, usize) {
+        self.board_size
+    }
+
     fn blank_flower() -> String {
         TextBlueprint::new()
             .with_text_color(AnsiColor::from_gray_value(12).expect("Couldn't get blank color"))
@@ -51,12 +133,62 @@ impl Board {
     }
 
     pub fn get_flower_mut(&mut self, pos: (usize, usize)) -> Option<&mut Option<AnyFlower>> {
-        Some(self.board.get_mut(pos.1)?.get_mut(pos.0)?)
+        self.board.get_mut(pos.1)?.get_mut(pos.0)
     }
 
+    /// Puts a flower without a recorded lineage in a cell, or empties it
     pub fn set_flower(&mut self, pos: (usize, usize), new_flower: Option<AnyFlower>) {
+        self.plant(pos, new_flower, None);
+    }
+
+    pub fn plant(
+        &mut self,
+        pos: (usize, usize),
+        new_flower: Option<AnyFlower>,
+        lineage: Option<Lineage>,
+    ) {
         if let Some(f) = self.get_flower_mut(pos) {
             *f = new_flower;
+            // a new flower hasn't been watered yet
+            self.watered[pos.1][pos.0] = false;
+            self.lineages[pos.1][pos.0] = lineage;
+        }
+    }
+
+    pub fn lineage(&self, pos: (usize, usize)) -> Option<Lineage> {
+        *self.lineages.get(pos.1)?.get(pos.0)?
+    }
+
+    /// Takes the flower out of a cell, leaving it empty
+    pub fn take_flower(&mut self, pos: (usize, usize)) -> Option<(AnyFlower, Option<Lineage>)> {
+        let flower = self.get_flower(pos)??;
+        let lineage = self.lineage(pos);
+        self.set_flower(pos, None);
+        Some((flower, lineage))
+    }
+
+    pub fn is_watered(&self, pos: (usize, usize)) -> bool {
+        self.watered
+            .get(pos.1)
+            .and_then(|row| row.get(pos.0))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Waters the flower at a position. Empty cells stay dry
+    pub fn water(&mut self, pos: (usize, usize)) {
+        if let Some(Some(_)) = self.get_flower(pos) {
+            self.watered[pos.1][pos.0] = true;
+        }
+    }
+
+    /// Waters the flower under the cursor, or every flower around it with the watering can's area mode
+    pub fn water_at_cursor(&mut self, area: bool) {
+        self.water(self.cursor_pos);
+        if area {
+            for pos in self.neighbors(self.cursor_pos) {
+                self.water(pos);
+            }
         }
     }
 
@@ -64,6 +196,23 @@ impl Board {
         self.set_flower(self.cursor_pos, new_flower);
     }
 
+    pub fn held(&self) -> Option<AnyFlower> {
+        self.held.as_ref().map(|(f, _)| f.clone())
+    }
+
+    /// Picks up the flower under the cursor, or puts the held one down, swapping it with whatever is there
+    pub fn pick_up_or_put_down(&mut self) {
+        let pos = self.cursor_pos;
+        if self.get_flower(pos).is_none() {
+            return;
+        }
+        let on_board = self.take_flower(pos);
+        if let Some((flower, lineage)) = self.held.take() {
+            self.plant(pos, Some(flower), lineage);
+        }
+        self.held = on_board;
+    }
+
     pub fn flower_string(
         &self,
         f: Option<AnyFlower>,
@@ -74,17 +223,132 @@ impl Board {
             flower.to_string(flower_context)
         });
 
-        if self.cursor_pos == position {
-            Self::selected_blueprint().apply(&unselected_flower_string)
-        } else {
-            unselected_flower_string.to_string()
+        match (self.cursor_pos == position, self.is_watered(position)) {
+            (true, _) if self.held.is_some() => {
+                Self::holding_blueprint().apply(unselected_flower_string)
+            }
+            (true, true) => Self::selected_watered_blueprint().apply(unselected_flower_string),
+            (true, false) => Self::selected_blueprint().apply(unselected_flower_string),
+            (false, true) => Self::watered_blueprint().apply(unselected_flower_string),
+            (false, false) => unselected_flower_string.to_string(),
         }
     }
 
+    pub fn to_saved(&self) -> SavedBoard {
+        SavedBoard {
+            flowers: self
+                .board
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|f| f.as_ref().map(SavedFlower::from_flower))
+                        .collect()
+                })
+                .collect(),
+            watered: self.watered.clone(),
+            lineages: self.lineages.clone(),
+            cursor_pos: self.cursor_pos,
+            held: self
+                .held
+                .as_ref()
+                .map(|(f, lineage)| (SavedFlower::from_flower(f), *lineage)),
+        }
+    }
+
+    /// Errors if the saved board is empty, not rectangular, or has flowers that can't be brought back
+    pub fn from_saved(saved: SavedBoard, flower_context: &FlowerContext) -> Result<Board, String> {
+        let height = saved.flowers.len();
+        let width = saved.flowers.first().map_or(0, |row| row.len());
+        let mut board = Board::new((width, height)).ok_or("it's empty")?;
+        if saved.flowers.iter().any(|row| row.len() != width)
+            || saved.watered.len() != height
+            || saved.watered.iter().any(|row| row.len() != width)
+            || saved.lineages.len() != height
+            || saved.lineages.iter().any(|row| row.len() != width)
+        {
+            return Err(format!("not everything is {}x{}", width, height));
+        }
+
+        board.board = saved
+            .flowers
+            .into_iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.into_iter()
+                    .enumerate()
+                    .map(|(x, f)| match f {
+                        Some(f) => f
+                            .to_flower(flower_context)
+                            .map(Some)
+                            .map_err(|e| format!("the flower at {},{}: {}", x + 1, y + 1, e)),
+                        None => Ok(None),
+                    })
+                    .collect::<Result<Vec<Option<AnyFlower>>, String>>()
+            })
+            .collect::<Result<Grid<Option<AnyFlower>>, String>>()?;
+        board.watered = saved.watered;
+        board.lineages = saved.lineages;
+        board.held = match saved.held {
+            Some((f, lineage)) => Some((
+                f.to_flower(flower_context)
+                    .map_err(|e| format!("the flower being held: {}", e))?,
+                lineage,
+            )),
+            None => None,
+        };
+        board.cursor_pos = (
+            saved.cursor_pos.0.min(width - 1),
+            saved.cursor_pos.1.min(height - 1),
+        );
+        Ok(board)
+    }
+
+    /// Every flower on the board with its position
+    pub fn flowers(&self) -> Vec<((usize, usize), AnyFlower)> {
+        self.board
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(x, f)| Some(((x, y), f.clone()?)))
+            })
+            .collect()
+    }
+
+    pub fn cursor_pos(&self) -> (usize, usize) {
+        self.cursor_pos
+    }
+
+    pub fn flower_at_cursor(&self) -> Option<AnyFlower> {
+        self.get_flower(self.cursor_pos).flatten()
+    }
+
     pub fn set_cursor(&mut self, new_pos: (usize, usize)) {
         self.cursor_pos = new_pos;
     }
 
+    /// Moves the cursor a screen's worth of cells, like for page up and down
+    pub fn move_cursor_by_page(&mut self, direction: (isize, isize)) {
+        let (width, height) = self.view_size;
+        self.move_cursor((direction.0 * width as isize, direction.1 * height as isize));
+    }
+
+    /// Moves the cursor to the first or last cell of its row
+    pub fn jump_to_row_end(&mut self, right: bool) {
+        let x = if right { self.board_size.0 - 1 } else { 0 };
+        self.cursor_pos = (x, self.cursor_pos.1);
+    }
+
+    pub fn view_size(&self) -> (usize, usize) {
+        self.view_size
+    }
+
+    /// Whether some of the board is off screen
+    pub fn is_scrolled(&self) -> bool {
+        self.view_size != self.board_size
+    }
+
     pub fn move_cursor(&mut self, d_pos: (isize, isize)) {
         let new_x = (self.cursor_pos.0 as isize + d_pos.0).clamp(0, self.board_size.0 as isize - 1)
             as usize;
@@ -93,13 +357,154 @@ impl Board {
         self.cursor_pos = (new_x, new_y);
     }
 
-    pub fn to_grid(&self, flower_context: &FlowerContext) -> Grid<String> {
+    /// The positions around a cell (diagonals included) that are on the board
+    fn neighbors(&self, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut neighbors = vec![];
+        for dy in -1..=1isize {
+            for dx in -1..=1isize {
+                if (dx, dy) == (0, 0) {
+                    continue;
+                }
+                let x = pos.0 as isize + dx;
+                let y = pos.1 as isize + dy;
+                if (0..self.board_size.0 as isize).contains(&x)
+                    && (0..self.board_size.1 as isize).contains(&y)
+                {
+                    neighbors.push((x as usize, y as usize));
+                }
+            }
+        }
+        neighbors
+    }
+
+    /// Simulates a day: every flower gets a chance to breed with a random neighbor of the same type,
+    /// the child landing on an empty cell next to one of its parents.
+    /// A flower can only breed once a day, and children only start breeding the day after.
+    /// Watered flowers are more likely to breed, and everything dries up once the day is over.
+    /// Children are recorded in the pedigree as born on `day`.
+    pub fn tick(
+        &mut self,
+        rng: &mut impl Rng,
+        pedigree: &mut Pedigree,
+        day: u32,
+    ) -> Vec<BreedEvent> {
+        let mut positions: Vec<(usize, usize)> = (0..self.board_size.1)
+            .flat_map(|y| (0..self.board_size.0).map(move |x| (x, y)))
+            .filter(|&pos| matches!(self.get_flower(pos), Some(Some(_))))
+            .collect();
+        positions.shuffle(rng);
+
+        let mut bred: Vec<(usize, usize)> = vec![];
+        let mut events = vec![];
+
+        for pos in positions {
+            if bred.contains(&pos) {
+                continue;
+            }
+            let Some(Some(flower)) = self.get_flower(pos) else {
+                continue;
+            };
+            let chance = if self.is_watered(pos) {
+                BREED_CHANCE * WATERED_BREED_MULTIPLIER
+            } else {
+                BREED_CHANCE
+            };
+            if !rng.random_bool(chance.min(1.0)) {
+                continue;
+            }
+
+            let partners: Vec<((usize, usize), AnyFlower)> = self
+                .neighbors(pos)
+                .into_iter()
+                .filter(|n| !bred.contains(n))
+                .filter_map(|n| Some((n, self.get_flower(n)??)))
+                .filter(|(_, f)| {
+                    f.info().flower_type() == flower.info().flower_type()
+                        && f.genotype().can_cross(&flower.genotype())
+                })
+                .collect();
+            let Some((partner_pos, partner)) = partners.choose(rng).cloned() else {
+                continue;
+            };
+
+            let mut empty: Vec<(usize, usize)> = self
+                .neighbors(pos)
+                .into_iter()
+                .chain(self.neighbors(partner_pos))
+                .filter(|&n| matches!(self.get_flower(n), Some(None)))
+                .collect();
+            empty.sort();
+            empty.dedup();
+            let Some(&child_pos) = empty.choose(rng) else {
+                continue;
+            };
+
+            let genotype = flower
+                .genotype()
+                .cross_with(&partner.genotype(), rng)
+                .expect("Couldn't cross-breed flowers...");
+            let child = flower.with_genotype(genotype);
+            let lineage =
+                pedigree.record_birth(&child, (self.lineage(pos), self.lineage(partner_pos)), day);
+            self.plant(child_pos, Some(child.clone()), Some(lineage));
+
+            bred.extend([pos, partner_pos, child_pos]);
+            events.push(BreedEvent {
+                parents: (pos, partner_pos),
+                child_pos,
+                child,
+            });
+        }
+
+        self.watered
+            .iter_mut()
+            .for_each(|row| row.iter_mut().for_each(|w| *w = false));
+
+        events
+    }
+
+    /// Draws the part of the board around the cursor that fits in `size`, two cells across for every flower
+    pub fn to_grid(
+        &mut self,
+        flower_context: &FlowerContext,
+        size: (usize, usize),
+    ) -> Grid<String> {
+        self.view_size = (
+            ((size.0.saturating_sub(1)) / 2).clamp(1, self.board_size.0),
+            size.1.clamp(1, self.board_size.1),
+        );
+        // scroll just enough to keep the cursor in view
+        let follow = |view: usize, cursor: usize, view_size: usize, board_size: usize| {
+            let view = view.min(cursor).max((cursor + 1).saturating_sub(view_size));
+            view.min(board_size - view_size)
+        };
+        self.view_pos = (
+            follow(
+                self.view_pos.0,
+                self.cursor_pos.0,
+                self.view_size.0,
+                self.board_size.0,
+            ),
+            follow(
+                self.view_pos.1,
+                self.cursor_pos.1,
+                self.view_size.1,
+                self.board_size.1,
+            ),
+        );
+
+        let (view_x, view_y) = self.view_pos;
+        let (view_width, view_height) = self.view_size;
         self.board
             .iter()
             .enumerate()
+            .skip(view_y)
+            .take(view_height)
             .map(|(y, fs)| {
                 fs.iter()
                     .enumerate()
+                    .skip(view_x)
+                    .take(view_width)
                     .map(|(x, f)| self.flower_string(f.clone(), (x, y), flower_context))
                     .zip([" ".to_string()].into_iter().cycle())
                     .flat_map(|(s1, s2)| [s2, s1].into_iter())
@@ -108,4 +513,310 @@ impl Board {
             .rev()
             .collect()
     }
+
+    /// An overview of the whole board squeezed into `size`, shaded by how many flowers each part has,
+    /// with the part on screen and the cursor highlighted
+    pub fn minimap(&self, size: (usize, usize)) -> Grid<String> {
+        const SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+        let (width, height) = (
+            size.0.min(self.board_size.0).max(1),
+            size.1.min(self.board_size.1).max(1),
+        );
+        // the board cells a map cell covers along one side
+        let span = |i: usize, map_size: usize, board_size: usize| {
+            i * board_size / map_size
+                ..((i + 1) * board_size / map_size).max(i * board_size / map_size + 1)
+        };
+        let overlaps = |a: &std::ops::Range<usize>, start: usize, len: usize| {
+            a.start < start + len && start < a.end
+        };
+
+        (0..height)
+            .rev()
+            .map(|my| {
+                let ys = span(my, height, self.board_size.1);
+                let mut row: Vec<String> = (0..width)
+                    .map(|mx| {
+                        let xs = span(mx, width, self.board_size.0);
+                        let cells = xs.len() * ys.len();
+                        let flowers = ys
+                            .clone()
+                            .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                            .filter(|&pos| matches!(self.get_flower(pos), Some(Some(_))))
+                            .count();
+                        let shade = SHADES[(flowers * 4).div_ceil(cells)];
+
+                        let in_view = overlaps(&xs, self.view_pos.0, self.view_size.0)
+                            && overlaps(&ys, self.view_pos.1, self.view_size.1);
+                        if xs.contains(&self.cursor_pos.0) && ys.contains(&self.cursor_pos.1) {
+                            Self::minimap_cursor_blueprint().apply(shade)
+                        } else if in_view {
+                            Self::view_blueprint().apply(shade)
+                        } else {
+                            shade.to_string()
+                        }
+                    })
+                    .collect();
+                row.resize(size.0, " ".to_string());
+                row
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::{ACNHFlowerType, FlowerType};
+    use crate::ui::backend::Frame;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn roses(flower_context: &FlowerContext) -> (AnyFlower, AnyFlower) {
+        let seeds = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose));
+        (seeds[0].clone(), seeds[1].clone())
+    }
+
+    #[test]
+    fn moved_flowers_keep_their_genes_and_lineage() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let (red, white) = roses(&flower_context);
+        let mut pedigree = Pedigree::new();
+        let red_lineage = pedigree.record_seed(&red, 1);
+        let white_lineage = pedigree.record_seed(&white, 1);
+        let mut board = Board::new((3, 3)).unwrap();
+        board.plant((0, 0), Some(red.clone()), Some(red_lineage));
+        board.plant((2, 2), Some(white.clone()), Some(white_lineage));
+
+        // picking up leaves the cell empty
+        board.pick_up_or_put_down();
+        assert_eq!(board.held().unwrap().genotype(), red.genotype());
+        assert!(matches!(board.get_flower((0, 0)), Some(None)));
+        assert_eq!(board.lineage((0, 0)), None);
+
+        // putting down on an empty cell leaves nothing in hand
+        board.set_cursor((1, 1));
+        board.pick_up_or_put_down();
+        assert!(board.held().is_none());
+        assert_eq!(board.flower_at_cursor().unwrap().genotype(), red.genotype());
+        assert_eq!(board.lineage((1, 1)), Some(red_lineage));
+
+        // putting down on another flower swaps them
+        board.pick_up_or_put_down();
+        board.set_cursor((2, 2));
+        board.pick_up_or_put_down();
+        assert_eq!(board.flower_at_cursor().unwrap().genotype(), red.genotype());
+        assert_eq!(board.lineage((2, 2)), Some(red_lineage));
+        assert_eq!(board.held().unwrap().genotype(), white.genotype());
+        board.set_cursor((0, 2));
+        board.pick_up_or_put_down();
+        assert_eq!(board.lineage((0, 2)), Some(white_lineage));
+        assert!(board.held().is_none());
+    }
+
+    #[test]
+    fn breeding_follows_the_rules_every_day() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let (red, white) = roses(&flower_context);
+        let cosmos = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Cosmos))[0].clone();
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut pedigree = Pedigree::new();
+        let mut board = Board::new((8, 8)).unwrap();
+        // roses and cosmos mixed together, with a few gaps for children
+        for y in 0..8 {
+            for x in 0..8 {
+                let flower = match (x + 3 * y) % 5 {
+                    0 => None,
+                    1 | 3 => Some(red.clone()),
+                    2 => Some(white.clone()),
+                    _ => Some(cosmos.clone()),
+                };
+                board.set_flower((x, y), flower);
+            }
+        }
+
+        let mut children = 0;
+        for day in 1..=10 {
+            let before: Vec<Vec<Option<FlowerType>>> = (0..8)
+                .map(|y| {
+                    (0..8)
+                        .map(|x| Some(board.get_flower((x, y))??.info().flower_type()))
+                        .collect()
+                })
+                .collect();
+            let species = |(x, y): (usize, usize)| before[y][x];
+            let events = board.tick(&mut rng, &mut pedigree, day);
+            children += events.len();
+
+            let mut bred = vec![];
+            for event in &events {
+                let (a, b) = event.parents();
+                let child = event.child_pos();
+                assert!(species(a).is_some() && species(a) == species(b));
+                assert!(board.neighbors(a).contains(&b));
+                assert_eq!(species(child), None, "a child landed on a flower");
+                assert!(board.neighbors(a).contains(&child) || board.neighbors(b).contains(&child));
+                assert_eq!(Some(event.child().info().flower_type()), species(a));
+                assert_eq!(board.lineage(child).unwrap().born_on(), day);
+                // each flower breeds once, and a child born today doesn't breed until tomorrow
+                assert!(
+                    !bred.contains(&a) && !bred.contains(&b),
+                    "{:?} bred twice",
+                    event.parents()
+                );
+                bred.extend([a, b, child]);
+            }
+        }
+        assert!(children > 0);
+    }
+
+    #[test]
+    fn area_watering_wets_the_flowers_around_the_cursor() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let (red, _) = roses(&flower_context);
+        let mut board = Board::new((4, 4)).unwrap();
+        for pos in [(0, 0), (1, 1), (2, 2), (3, 3)] {
+            board.set_flower(pos, Some(red.clone()));
+        }
+
+        board.set_cursor((1, 1));
+        board.water_at_cursor(false);
+        assert!(board.is_watered((1, 1)));
+        assert!(!board.is_watered((0, 0)));
+
+        board.water_at_cursor(true);
+        assert!(board.is_watered((0, 0)) && board.is_watered((2, 2)));
+        // out of reach
+        assert!(!board.is_watered((3, 3)));
+        // empty cells stay dry
+        assert!(!board.is_watered((1, 0)) && !board.is_watered((2, 1)));
+        board.set_cursor((1, 0));
+        board.water_at_cursor(false);
+        assert!(!board.is_watered((1, 0)));
+    }
+
+    #[test]
+    fn watered_flowers_breed_more_and_dry_up_overnight() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let (red, white) = roses(&flower_context);
+        let mut rng = StdRng::seed_from_u64(6);
+        let days = 2000;
+        let mut bred = |watered: bool| {
+            let mut births = 0;
+            for day in 0..days {
+                let mut board = Board::new((3, 1)).unwrap();
+                board.set_flower((0, 0), Some(red.clone()));
+                board.set_flower((1, 0), Some(white.clone()));
+                if watered {
+                    board.set_cursor((0, 0));
+                    board.water_at_cursor(true);
+                }
+                births += board.tick(&mut rng, &mut Pedigree::new(), day).len();
+                // nothing is still watered the next day
+                assert!((0..3).all(|x| !board.is_watered((x, 0))));
+            }
+            births as f64 / days as f64
+        };
+        // either of the two flowers can start breeding
+        let chance = |c: f64| 1.0 - (1.0 - c).powi(2);
+        let dry = bred(false);
+        let wet = bred(true);
+        assert!((dry - chance(BREED_CHANCE)).abs() < 0.04, "{}", dry);
+        assert!(
+            (wet - chance(BREED_CHANCE * WATERED_BREED_MULTIPLIER)).abs() < 0.04,
+            "{}",
+            wet
+        );
+    }
+
+    #[test]
+    fn the_view_follows_the_cursor_across_a_big_board() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let mut board = Board::new((64, 64)).unwrap();
+        // ten cells across and ten down
+        let size = (21, 10);
+        board.to_grid(&flower_context, size);
+        assert_eq!(board.view_size(), (10, 10));
+        assert!(board.is_scrolled());
+
+        // the view only moves once the cursor walks off its edge
+        board.move_cursor((9, 9));
+        board.to_grid(&flower_context, size);
+        assert_eq!(board.view_pos, (0, 0));
+        board.move_cursor((1, 0));
+        board.to_grid(&flower_context, size);
+        assert_eq!(board.view_pos, (1, 0));
+        board.move_cursor((-5, 0));
+        board.to_grid(&flower_context, size);
+        assert_eq!(board.view_pos, (1, 0));
+        board.move_cursor((-5, 0));
+        board.to_grid(&flower_context, size);
+        assert_eq!(board.view_pos, (0, 0));
+
+        // and stops at the far edges
+        board.jump_to_row_end(true);
+        board.move_cursor((0, 100));
+        board.to_grid(&flower_context, size);
+        assert_eq!(board.cursor_pos(), (63, 63));
+        assert_eq!(board.view_pos, (54, 54));
+        board.jump_to_row_end(false);
+        board.to_grid(&flower_context, size);
+        assert_eq!(board.cursor_pos(), (0, 63));
+        assert_eq!(board.view_pos, (0, 54));
+    }
+
+    #[test]
+    fn page_moves_stop_at_the_edge_of_the_board() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let mut board = Board::new((64, 64)).unwrap();
+        board.to_grid(&flower_context, (21, 10));
+        board.move_cursor_by_page((0, 1));
+        assert_eq!(board.cursor_pos(), (0, 10));
+        board.move_cursor_by_page((1, 0));
+        assert_eq!(board.cursor_pos(), (10, 10));
+        for _ in 0..7 {
+            board.move_cursor_by_page((1, 1));
+        }
+        assert_eq!(board.cursor_pos(), (63, 63));
+        board.move_cursor_by_page((-1, 0));
+        assert_eq!(board.cursor_pos(), (53, 63));
+        for _ in 0..7 {
+            board.move_cursor_by_page((-1, -1));
+        }
+        assert_eq!(board.cursor_pos(), (0, 0));
+    }
+
+    #[test]
+    fn the_minimap_is_shaded_by_how_full_each_part_is() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let (red, _) = roses(&flower_context);
+        let mut board = Board::new((64, 64)).unwrap();
+        // each map cell covers eight by eight board cells, so 64 of them
+        let mut fill = |block: usize, count: usize| {
+            for i in 0..count {
+                board.set_flower((block * 8 + i % 8, i / 8), Some(red.clone()));
+            }
+        };
+        fill(0, 64);
+        fill(1, 33);
+        fill(2, 16);
+        fill(3, 1);
+        board.set_cursor((63, 63));
+        board.to_grid(&flower_context, (21, 10));
+
+        let frame = Frame::from_grid(&board.minimap((8, 8)), "");
+        assert_eq!(frame.lines().len(), 8);
+        assert_eq!(frame.lines()[0], "········");
+        assert_eq!(frame.lines()[7], "█▓░░····");
+        // the cursor's corner, and the rest of the view around it
+        assert_eq!(frame.style_at((7, 0)), "48;5;61");
+        assert_eq!(frame.style_at((6, 1)), "48;5;238");
+        assert_eq!(frame.style_at((5, 1)), "");
+        assert_eq!(frame.style_at((0, 7)), "");
+
+        // a map bigger than the board shows each cell once
+        let small = Board::new((3, 2)).unwrap();
+        let frame = Frame::from_grid(&small.minimap((8, 8)), "");
+        assert_eq!(frame.lines().len(), 2);
+    }
 }
@@ -1,49 +1,104 @@
-use std::io::StdoutLock;
-use std::io::Write;
-
-use crate::ui::{Grid, to_grid};
+use crate::ui::{
+    Grid,
+    backend::Backend,
+    empty_string_grid,
+    layout::{Layout, Rect},
+    text_row,
+};
 
 pub struct Display {
+    /// None when the screen is too small for any layout
+    layout: Option<Layout>,
     display_size: (usize, usize),
+    /// The borders and titles every frame starts from
+    background: Grid<String>,
     display: Grid<String>,
-    board_size: (usize, usize),
 }
 
 impl Display {
-    pub fn new() -> Display {
-        let display_string: String = Self::grid_template()
-            .chars()
-            .take_while(|&c| c != '#')
-            .collect();
-        let display_size = (
-            display_string
-                .chars()
-                .position(|c| c == '\n')
-                .expect("Could not find a new line in display template...")
-                + 1,
-            display_string.matches("\n").count(),
-        );
-        let board_length = display_string
-            .char_indices()
-            .filter(|c| c.1 == '+')
-            .nth(1)
-            .expect("Could not find a second + in display template...")
-            .0
-            / 2
-            - 1;
-        let display = to_grid(display_string, display_size.0);
+    pub fn new(layout: Layout) -> Display {
+        let display_size = layout.size();
+        let mut background = empty_string_grid(display_size);
+        for (rect, title) in layout.boxes() {
+            Self::draw_box(&mut background, rect);
+            if let Some(title) = title {
+                let padding = (rect.width.saturating_sub(title.len())) / 2;
+                let row = text_row(
+                    &format!("{}{}", " ".repeat(padding), title),
+                    rect.width,
+                    None,
+                );
+                background[rect.y].splice(rect.x..rect.right(), row);
+            }
+        }
 
         Display {
+            layout: Some(layout),
             display_size,
-            display,
-            board_size: (board_length, board_length),
+            display: background.clone(),
+            background,
+        }
+    }
+
+    /// A `size` frame saying how big the terminal needs to be, instead of a layout that doesn't fit
+    pub fn too_small(
+        size: (usize, usize),
+        have: (usize, usize),
+        needed: (usize, usize),
+    ) -> Display {
+        let (width, height) = size;
+        let lines = [
+            "terminal too small".to_string(),
+            format!("need {}x{}", needed.0, needed.1),
+            format!("have {}x{}", have.0, have.1),
+        ];
+        let mut background = empty_string_grid(size);
+        let top = height.saturating_sub(lines.len()) / 2;
+        for (row, line) in background.iter_mut().skip(top).zip(lines.iter()) {
+            let padding = width.saturating_sub(line.len()) / 2;
+            *row = text_row(&format!("{}{}", " ".repeat(padding), line), width, None);
+        }
+
+        Display {
+            layout: None,
+            display_size: size,
+            display: background.clone(),
+            background,
         }
     }
 
-    pub fn stamp_row(&mut self, row: &Vec<String>, start: (usize, usize)) {
+    /// Draws a border around the rect, joining it up with borders already there
+    fn draw_box(grid: &mut Grid<String>, rect: Rect) {
+        let (left, top, right, bottom) = (rect.x - 1, rect.y - 1, rect.right(), rect.bottom());
+        let mut draw = |x: usize, y: usize, c: &str| {
+            let cell = &mut grid[y][x];
+            *cell = match cell.as_str() {
+                " " => c.to_string(),
+                old if old == c => c.to_string(),
+                // crossing another border
+                _ => "+".to_string(),
+            };
+        };
+        for x in left..=right {
+            let edge = if x == left || x == right { "+" } else { "-" };
+            draw(x, top, edge);
+            draw(x, bottom, edge);
+        }
+        for y in rect.y..bottom {
+            draw(left, y, "|");
+            draw(right, y, "|");
+        }
+    }
+
+    pub fn stamp_row(&mut self, row: &[String], start: (usize, usize)) {
         self.display
             .get_mut(start.1)
-            .expect(format!("Could not get mutable reference to the display at row: {}", start.1).as_str())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Could not get mutable reference to the display at row: {}",
+                    start.1
+                )
+            })
             .iter_mut()
             .skip(start.0)
             .zip(row.iter())
@@ -59,35 +114,29 @@ impl Display {
     }
 
     pub fn reset_display(&mut self) {
-        self.display = to_grid(Self::grid_template(), self.display_size.0);
+        self.display = self.background.clone();
     }
 
-    fn grid_template() -> String {
-        include_str!("grid_template.txt").to_string()
+    pub fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
     }
 
-    pub fn board_size(&self) -> (usize, usize) {
-        self.board_size
+    pub fn size(&self) -> (usize, usize) {
+        self.display_size
     }
 
     pub fn to_string(&self) -> String {
         self.display
             .iter()
             .map(|strings| {
-                strings
-                    .iter()
-                    .map(|cs| cs.chars())
-                    .flatten()
-                    .collect::<String>()
+                let mut line: String = strings.iter().flat_map(|cs| cs.chars()).collect();
+                line.push('\n');
+                line
             })
             .collect()
     }
 
-    pub fn refresh(&mut self, lock: &mut StdoutLock<'static>) {
-        // absolute buffonery: for each line, clear it and print on that line using ansi trickery :3
-        for (i, s) in self.display.iter().enumerate() {
-            let text: String = s.iter().flat_map(|cs| cs.chars()).collect();
-            writeln!(lock, "\x1B[{}H{}", i + 1, text);
-        }
+    pub fn refresh(&self, backend: &mut dyn Backend, status: &str) -> std::io::Result<()> {
+        backend.draw(&self.display, status)
     }
 }
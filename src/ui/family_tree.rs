@@ -0,0 +1,192 @@
+use crate::flowers::flower::FlowerContext;
+use crate::flowers::lineage::{Lineage, Pedigree};
+
+use super::{
+    Grid,
+    terminal::{AnsiColor, TextBlueprint},
+    text_row,
+};
+
+/// How many generations back the tree goes
+pub const MAX_TREE_DEPTH: usize = 6;
+
+/// The ancestors of a flower, the flower itself at the top and each of its parents below it
+pub struct FamilyTree {
+    root: Lineage,
+    /// The first line shown, for trees taller than the view
+    scroll: usize,
+}
+
+impl FamilyTree {
+    pub fn label_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_text_color(AnsiColor::from_gray_value(12).expect("Couldn't get label color"))
+            .to_owned()
+    }
+
+    pub fn new(root: Lineage) -> FamilyTree {
+        FamilyTree { root, scroll: 0 }
+    }
+
+    pub fn scroll(&mut self, d: isize) {
+        self.scroll = (self.scroll as isize + d).max(0) as usize;
+    }
+
+    /// Adds the line of a flower and then the lines of its parents.
+    /// `indent` is the tree to the left of the line and `branch` connects it to its child
+    fn push_lines(
+        lines: &mut Grid<String>,
+        id: u64,
+        indent: &[String],
+        branch: &str,
+        depth: usize,
+        pedigree: &Pedigree,
+        flower_context: &FlowerContext,
+    ) {
+        let cells = |text: &str, blueprint: Option<&TextBlueprint>| {
+            text_row(text, text.chars().count(), blueprint)
+        };
+        let label = Self::label_blueprint();
+
+        let mut line = indent.to_vec();
+        line.extend(cells(branch, None));
+        let record = pedigree.get(id);
+        match record.and_then(|r| Some((r.lineage(), r.flower(flower_context)?))) {
+            Some((lineage, flower)) => {
+                let color = flower_context
+                    .phenotype_name(flower.info().flower_type(), &flower.genotype())
+                    .unwrap_or_default();
+                line.push(flower.phenotype(flower_context).to_string());
+                line.extend(cells(&format!(" #{} {}", id, color), None));
+                let details = match lineage.parents() {
+                    (None, None) if lineage.generation() == 0 => {
+                        format!(" seed, day {}", lineage.born_on())
+                    }
+                    _ => format!(" gen {}, day {}", lineage.generation(), lineage.born_on()),
+                };
+                line.extend(cells(&details, Some(&label)));
+            }
+            None => line.extend(cells(&format!("? #{}", id), None)),
+        }
+        lines.push(line);
+
+        // parents without a record are left out
+        let parents: Vec<u64> = match record.map(|r| r.lineage().parents()) {
+            Some((a, b)) => a.into_iter().chain(b).collect(),
+            None => vec![],
+        };
+        if parents.is_empty() {
+            return;
+        }
+        let mut parent_indent = indent.to_vec();
+        parent_indent.extend(cells(
+            match branch {
+                "├─" => "│ ",
+                "└─" => "  ",
+                _ => "",
+            },
+            None,
+        ));
+        if depth + 1 >= MAX_TREE_DEPTH {
+            let mut more = parent_indent;
+            more.extend(cells("└─…", Some(&label)));
+            lines.push(more);
+            return;
+        }
+        for (i, &parent) in parents.iter().enumerate() {
+            let parent_branch = if i + 1 == parents.len() {
+                "└─"
+            } else {
+                "├─"
+            };
+            Self::push_lines(
+                lines,
+                parent,
+                &parent_indent,
+                parent_branch,
+                depth + 1,
+                pedigree,
+                flower_context,
+            );
+        }
+    }
+
+    pub fn to_grid(
+        &mut self,
+        pedigree: &Pedigree,
+        flower_context: &FlowerContext,
+        size: (usize, usize),
+    ) -> Grid<String> {
+        let (width, height) = size;
+        let mut lines: Grid<String> = vec![];
+        Self::push_lines(
+            &mut lines,
+            self.root.id(),
+            &[],
+            "",
+            0,
+            pedigree,
+            flower_context,
+        );
+
+        self.scroll = self.scroll.min(lines.len().saturating_sub(height));
+        let mut grid: Grid<String> = lines
+            .into_iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|mut line| {
+                line.resize(width, " ".to_string());
+                line.truncate(width);
+                line
+            })
+            .collect();
+        grid.resize(height, text_row("", width, None));
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::{ACNHFlowerType, FlowerType};
+    use crate::ui::backend::Frame;
+
+    #[test]
+    fn parents_hang_under_their_child() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let seeds = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose));
+        let (red, white) = (&seeds[0], &seeds[1]);
+        let mut pedigree = Pedigree::new();
+        let a = pedigree.record_seed(red, 1);
+        let b = pedigree.record_seed(white, 1);
+        let child = pedigree.record_birth(red, (Some(a), Some(b)), 2);
+        // the other parent was never recorded, so it's left out
+        let grandchild = pedigree.record_birth(white, (Some(child), None), 3);
+
+        let mut tree = FamilyTree::new(grandchild);
+        let grid = tree.to_grid(&pedigree, &flower_context, (30, 6));
+        let frame = Frame::from_grid(&grid, "");
+        let lines: Vec<&str> = frame.lines().iter().map(|l| l.trim_end()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "@ #3 White gen 2, day 3",
+                "└─@ #2 Red gen 1, day 2",
+                "  ├─@ #0 Red seed, day 1",
+                "  └─@ #1 White seed, day 1",
+                "",
+                "",
+            ]
+        );
+        // the flower in its color, the details as labels
+        assert_eq!(frame.style_at((2, 1)), "38;5;160");
+        assert_eq!(frame.style_at((11, 0)), "38;5;244");
+        assert_eq!(frame.style_at((5, 0)), "");
+
+        // scrolling stops once the last line is at the bottom
+        tree.scroll(10);
+        let grid = tree.to_grid(&pedigree, &flower_context, (30, 2));
+        let frame = Frame::from_grid(&grid, "");
+        assert!(frame.lines()[1].contains("#1"));
+    }
+}
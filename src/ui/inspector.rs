@@ -0,0 +1,115 @@
+use crate::flowers::flower::{AnyFlower, FlowerContext};
+
+use super::{
+    Grid,
+    terminal::{AnsiColor, TextBlueprint},
+    text_row,
+};
+
+/// Shows everything about the flower under the board cursor
+pub struct Inspector {
+    size: (usize, usize),
+}
+
+impl Inspector {
+    pub fn label_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_text_color(AnsiColor::from_gray_value(12).expect("Couldn't get label color"))
+            .to_owned()
+    }
+
+    pub fn new(size: (usize, usize)) -> Inspector {
+        Inspector { size }
+    }
+
+    pub fn to_grid(
+        &self,
+        flower: Option<AnyFlower>,
+        watered: bool,
+        flower_context: &FlowerContext,
+    ) -> Grid<String> {
+        let (width, height) = self.size;
+        let mut grid: Grid<String> = vec![];
+
+        match flower {
+            None => grid.push(text_row(
+                "(nothing here)",
+                width,
+                Some(&Self::label_blueprint()),
+            )),
+            Some(flower) => {
+                let info = flower.info();
+                let genotype = flower.genotype();
+                let phenotype = flower.phenotype(flower_context);
+                let color = flower_context
+                    .phenotype_name(info.flower_type(), &genotype)
+                    .unwrap_or_default();
+                let letters = flower_context.gene_letters(info.flower_type());
+
+                let mut name_row = vec![phenotype.to_string(), " ".to_string()];
+                name_row.extend(text_row(&info.name(), width.saturating_sub(2), None));
+                grid.push(name_row);
+                grid.push(text_row(&color, width, Some(&phenotype.blueprint())));
+                grid.push(text_row("", width, None));
+                grid.push(text_row("genotype", width, Some(&Self::label_blueprint())));
+                grid.push(text_row(&genotype.to_allele_string(&letters), width, None));
+                grid.push(text_row(&genotype.to_code(), width, None));
+                if watered {
+                    grid.push(text_row(
+                        "watered",
+                        width,
+                        Some(TextBlueprint::new().with_text_color(AnsiColor::from_code(39))),
+                    ));
+                }
+            }
+        }
+
+        grid.resize(height, text_row("", width, None));
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::{ACNHFlowerType, FlowerType};
+    use crate::ui::backend::Frame;
+
+    #[test]
+    fn the_flower_is_shown_with_its_genotype() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let red = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose))[0].clone();
+        let inspector = Inspector::new((16, 7));
+
+        let frame = Frame::from_grid(&inspector.to_grid(Some(red), true, &flower_context), "");
+        let lines: Vec<&str> = frame.lines().iter().map(|l| l.trim_end()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "@ Rose (acnh)",
+                "Red",
+                "",
+                "genotype",
+                "RRyyWWSs",
+                "2001",
+                "watered"
+            ]
+        );
+        assert_eq!(frame.style_at((0, 0)), "38;5;160");
+        assert_eq!(frame.style_at((0, 1)), "38;5;160");
+        assert_eq!(frame.style_at((0, 3)), "38;5;244");
+        assert_eq!(frame.style_at((0, 4)), "");
+        assert_eq!(frame.style_at((0, 6)), "38;5;39");
+    }
+
+    #[test]
+    fn empty_cells_say_so() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let grid = Inspector::new((16, 7)).to_grid(None, false, &flower_context);
+        let frame = Frame::from_grid(&grid, "");
+        assert_eq!(frame.lines().len(), 7);
+        assert_eq!(frame.lines()[0].trim_end(), "(nothing here)");
+        assert_eq!(frame.style_at((0, 0)), "38;5;244");
+        assert!(frame.lines()[1..].iter().all(|l| l.trim().is_empty()));
+    }
+}
@@ -0,0 +1,351 @@
+use crate::flowers::flower::{AnyFlower, FlowerContext};
+use crate::flowers::lineage::Lineage;
+use crate::save::{SavedFlower, SavedItem};
+
+use super::{
+    Grid,
+    terminal::{AnsiColor, TextBlueprint},
+    text_row,
+};
+
+#[derive(Debug, Clone)]
+pub enum InventoryItem {
+    /// Shop seeds of one species and genotype
+    Seeds { flower: AnyFlower, count: usize },
+    /// A flower picked off the board
+    Picked {
+        flower: AnyFlower,
+        lineage: Option<Lineage>,
+    },
+}
+
+impl InventoryItem {
+    pub fn flower(&self) -> AnyFlower {
+        match self {
+            InventoryItem::Seeds { flower, .. } | InventoryItem::Picked { flower, .. } => {
+                flower.clone()
+            }
+        }
+    }
+
+    /// How many bells one seed or the picked flower sells for.
+    /// Seeds sell back for a quarter of what they cost, like in ACNH
+    pub fn sell_price(&self, flower_context: &FlowerContext) -> u32 {
+        match self {
+            InventoryItem::Seeds { flower, .. } => {
+                flower_context.seed_price(flower.info().flower_type()) / 4
+            }
+            InventoryItem::Picked { flower, .. } => flower_context.sell_price(flower),
+        }
+    }
+
+    fn to_saved(&self) -> SavedItem {
+        match self {
+            InventoryItem::Seeds { flower, count } => SavedItem::Seeds {
+                flower: SavedFlower::from_flower(flower),
+                count: *count,
+            },
+            InventoryItem::Picked { flower, lineage } => SavedItem::Picked {
+                flower: SavedFlower::from_flower(flower),
+                lineage: *lineage,
+            },
+        }
+    }
+
+    fn from_saved(saved: SavedItem, flower_context: &FlowerContext) -> Result<Self, String> {
+        Ok(match saved {
+            SavedItem::Seeds { flower, count } => InventoryItem::Seeds {
+                flower: flower.to_flower(flower_context)?,
+                count,
+            },
+            SavedItem::Picked { flower, lineage } => InventoryItem::Picked {
+                flower: flower.to_flower(flower_context)?,
+                lineage,
+            },
+        })
+    }
+}
+
+/// Seeds from the shop and flowers picked off the board, waiting to be planted
+pub struct Inventory {
+    items: Vec<InventoryItem>,
+    cursor: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inventory {
+    pub fn selected_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_background_color(AnsiColor::from_code(236))
+            .to_owned()
+    }
+
+    pub fn label_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_text_color(AnsiColor::from_gray_value(12).expect("Couldn't get label color"))
+            .to_owned()
+    }
+
+    pub fn new() -> Inventory {
+        Inventory {
+            items: vec![],
+            cursor: 0,
+        }
+    }
+
+    pub fn items(&self) -> &[InventoryItem] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Option<&InventoryItem> {
+        self.items.get(self.cursor)
+    }
+
+    pub fn move_cursor(&mut self, d: isize) {
+        self.cursor = (self.cursor as isize + d)
+            .clamp(0, self.items.len().saturating_sub(1) as isize) as usize;
+    }
+
+    /// Adds seeds to the stack of the same species and genotype, starting a new stack if there isn't one
+    pub fn add_seeds(&mut self, flower: AnyFlower, count: usize) {
+        let stack = self.items.iter_mut().find_map(|item| match item {
+            InventoryItem::Seeds { flower: f, count }
+                if f.info().flower_type() == flower.info().flower_type()
+                    && f.genotype() == flower.genotype() =>
+            {
+                Some(count)
+            }
+            _ => None,
+        });
+        match stack {
+            Some(stack) => *stack += count,
+            None => self.items.push(InventoryItem::Seeds { flower, count }),
+        }
+    }
+
+    pub fn add_picked(&mut self, flower: AnyFlower, lineage: Option<Lineage>) {
+        self.items.push(InventoryItem::Picked { flower, lineage });
+    }
+
+    /// Takes one seed or the picked flower out of the selected slot.
+    /// Seeds don't have a lineage until they're planted
+    pub fn take_selected(&mut self) -> Option<(AnyFlower, Option<Lineage>)> {
+        let taken = match self.items.get_mut(self.cursor)? {
+            InventoryItem::Seeds { flower, count } => {
+                *count -= 1;
+                (flower.clone(), None)
+            }
+            InventoryItem::Picked { flower, lineage } => (flower.clone(), *lineage),
+        };
+        if matches!(
+            self.items[self.cursor],
+            InventoryItem::Picked { .. } | InventoryItem::Seeds { count: 0, .. }
+        ) {
+            self.items.remove(self.cursor);
+            self.move_cursor(0);
+        }
+        Some(taken)
+    }
+
+    /// Throws away one seed or the picked flower in the selected slot
+    pub fn discard_selected(&mut self) {
+        self.take_selected();
+    }
+
+    pub fn to_saved(&self) -> Vec<SavedItem> {
+        self.items.iter().map(InventoryItem::to_saved).collect()
+    }
+
+    /// Errors saying which flower can't be brought back
+    pub fn from_saved(
+        saved: Vec<SavedItem>,
+        flower_context: &FlowerContext,
+    ) -> Result<Inventory, String> {
+        let items = saved
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                InventoryItem::from_saved(item, flower_context)
+                    .map_err(|e| format!("slot {}: {}", i + 1, e))
+            })
+            .collect::<Result<Vec<InventoryItem>, String>>()?;
+        Ok(Inventory { items, cursor: 0 })
+    }
+
+    /// A row of the slots and a row describing the selected one
+    pub fn to_grid(&self, flower_context: &FlowerContext, width: usize) -> Grid<String> {
+        let slots: Vec<Vec<String>> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let glyph = item.flower().to_string(flower_context);
+                let mut slot = match item {
+                    InventoryItem::Seeds { count, .. } => {
+                        let mut slot = vec![glyph];
+                        slot.extend(text_row(&count.to_string(), count.to_string().len(), None));
+                        slot
+                    }
+                    InventoryItem::Picked { .. } => vec![glyph],
+                };
+                if i == self.cursor {
+                    slot = slot
+                        .iter()
+                        .map(|s| Self::selected_blueprint().apply(s))
+                        .collect();
+                }
+                slot.push(" ".to_string());
+                slot
+            })
+            .collect();
+
+        // scroll so the selected slot is always shown
+        let mut first = 0;
+        if !slots.is_empty() {
+            while slots[first..=self.cursor]
+                .iter()
+                .map(Vec::len)
+                .sum::<usize>()
+                > width
+            {
+                first += 1;
+            }
+        }
+        let mut slot_row: Vec<String> = slots
+            .into_iter()
+            .skip(first)
+            .flatten()
+            .take(width)
+            .collect();
+        slot_row.resize(width, " ".to_string());
+
+        let details_row = match self.selected() {
+            None => text_row("(empty)", width, Some(&Self::label_blueprint())),
+            Some(item) => {
+                let flower = item.flower();
+                let flower_type = flower.info().flower_type();
+                let genotype = flower.genotype();
+                let color = flower_context
+                    .phenotype_name(flower_type, &genotype)
+                    .unwrap_or_default();
+                let letters = flower_context.gene_letters(flower_type);
+                let origin = match item {
+                    InventoryItem::Seeds { .. } => "seeds".to_string(),
+                    InventoryItem::Picked { lineage, .. } => match lineage {
+                        Some(l) => format!("#{} gen {}", l.id(), l.generation()),
+                        None => String::new(),
+                    },
+                };
+                // the origin goes last since it's the first thing to get cut off
+                let text = format!(
+                    "{} {} {} {} bells {}",
+                    color,
+                    genotype.to_allele_string(&letters),
+                    flower.info().name(),
+                    item.sell_price(flower_context),
+                    origin
+                );
+                text_row(&text, width, None)
+            }
+        };
+
+        vec![slot_row, details_row]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::{ACNHFlowerType, FlowerType};
+    use crate::flowers::lineage::Pedigree;
+
+    fn count(item: &InventoryItem) -> Option<usize> {
+        match item {
+            InventoryItem::Seeds { count, .. } => Some(*count),
+            InventoryItem::Picked { .. } => None,
+        }
+    }
+
+    #[test]
+    fn seeds_stack_by_species_and_genotype() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let roses = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose));
+        let mut inventory = Inventory::new();
+        inventory.add_seeds(roses[0].clone(), 2);
+        inventory.add_seeds(roses[1].clone(), 1);
+        inventory.add_seeds(roses[0].clone(), 3);
+        // picked flowers keep their own slot
+        inventory.add_picked(roses[0].clone(), None);
+        inventory.add_picked(roses[0].clone(), None);
+
+        let counts: Vec<Option<usize>> = inventory.items().iter().map(count).collect();
+        assert_eq!(counts, vec![Some(5), Some(1), None, None]);
+        assert_eq!(
+            inventory.items()[1].flower().genotype(),
+            roses[1].genotype()
+        );
+    }
+
+    #[test]
+    fn taking_the_last_of_a_slot_removes_it() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let roses = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose));
+        let lineage = Pedigree::new().record_seed(&roses[1], 1);
+        let mut inventory = Inventory::new();
+        assert!(inventory.take_selected().is_none());
+        inventory.add_seeds(roses[0].clone(), 2);
+        inventory.add_picked(roses[1].clone(), Some(lineage));
+
+        // seeds come out one at a time, without a lineage
+        let (flower, taken_lineage) = inventory.take_selected().unwrap();
+        assert_eq!(flower.genotype(), roses[0].genotype());
+        assert_eq!(taken_lineage, None);
+        assert_eq!(count(&inventory.items()[0]), Some(1));
+        inventory.take_selected();
+        assert_eq!(inventory.items().len(), 1);
+        assert_eq!(
+            inventory.selected().unwrap().flower().genotype(),
+            roses[1].genotype()
+        );
+
+        // picked flowers keep their lineage
+        assert_eq!(inventory.take_selected().unwrap().1, Some(lineage));
+        assert!(inventory.items().is_empty());
+        assert!(inventory.selected().is_none());
+    }
+
+    #[test]
+    fn discarding_keeps_the_cursor_on_a_slot() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let roses = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose));
+        let mut inventory = Inventory::new();
+        inventory.add_seeds(roses[0].clone(), 1);
+        inventory.add_seeds(roses[1].clone(), 2);
+        inventory.add_picked(roses[0].clone(), None);
+        inventory.move_cursor(10);
+        assert_eq!(inventory.cursor, 2);
+
+        // the last slot goes, so the cursor moves back onto the one before it
+        inventory.discard_selected();
+        assert_eq!(inventory.cursor, 1);
+        inventory.discard_selected();
+        assert_eq!(count(inventory.selected().unwrap()), Some(1));
+        inventory.discard_selected();
+        assert_eq!(inventory.cursor, 0);
+        assert_eq!(
+            inventory.selected().unwrap().flower().genotype(),
+            roses[0].genotype()
+        );
+        inventory.discard_selected();
+        assert!(inventory.items().is_empty());
+        assert_eq!(inventory.cursor, 0);
+        inventory.discard_selected();
+        inventory.move_cursor(-1);
+        assert_eq!(inventory.cursor, 0);
+    }
+}
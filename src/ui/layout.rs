@@ -0,0 +1,345 @@
+/// The smallest the board pane gets, so menu pages and the family tree still fit over it
+pub const MIN_BOARD_PANE: (usize, usize) = (19, 9);
+
+const SIDE_WIDTH: usize = 9;
+const SHOP_HEIGHT: usize = 3;
+const INSPECTOR_WIDTH: usize = 18;
+const STATS_WIDTH: usize = 20;
+const BAG_TITLE_WIDTH: usize = 8;
+const BAG_HEIGHT: usize = 2;
+const INSPECTOR_HEIGHT: usize = 7;
+/// The fewest rows a minimap is worth showing with
+const MIN_MAP_HEIGHT: usize = 3;
+/// The shop and menu column: a title, the shop, a title and the menu, with borders between them
+const PANELS_HEIGHT: usize = 1 + 1 + SHOP_HEIGHT + 1 + 1 + 1 + 1;
+
+/// Builds a layout from a board pane size, and whether it has the stats and the minimap
+type Arrangement = fn((usize, usize), bool, bool) -> Layout;
+
+/// The inside of a box on the screen, not counting its border
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn pos(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// The column of its right border
+    pub fn right(&self) -> usize {
+        self.x + self.width
+    }
+
+    /// The row of its bottom border
+    pub fn bottom(&self) -> usize {
+        self.y + self.height
+    }
+}
+
+/// Where every panel goes for a screen size. Panels keep their size and get moved around,
+/// side by side when the screen is wide and stacked under the board when it's tall.
+/// The board pane takes whatever room is left, up to the size of the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    size: (usize, usize),
+    board: Rect,
+    shop_title: Rect,
+    shop: Rect,
+    menu_title: Rect,
+    menu: Rect,
+    inspector_title: Rect,
+    inspector: Rect,
+    stats: Option<(Rect, Rect)>,
+    /// Under the inspector, when the board doesn't fit and there's room
+    map: Option<(Rect, Rect)>,
+    bag_title: Rect,
+    bag: Rect,
+}
+
+impl Layout {
+    /// The board pane for a board, two cells across for every flower
+    pub fn board_pane(board_size: (usize, usize)) -> (usize, usize) {
+        (
+            (board_size.0 * 2 + 1).max(MIN_BOARD_PANE.0),
+            board_size.1.max(MIN_BOARD_PANE.1),
+        )
+    }
+
+    /// The best layout that fits in `available`, shrinking the board pane down from `board_pane`
+    /// and leaving out the stats if they don't fit. None if nothing fits
+    pub fn fit(
+        available: (usize, usize),
+        board_pane: (usize, usize),
+        show_stats: bool,
+    ) -> Option<Layout> {
+        let stats_options: &[bool] = if show_stats { &[true, false] } else { &[false] };
+        let arrangements: [Arrangement; 2] = [Self::side_by_side, Self::stacked];
+        stats_options
+            .iter()
+            .flat_map(|&stats| arrangements.iter().map(move |&arrange| (arrange, stats)))
+            .find_map(|(arrange, stats)| {
+                let smallest = arrange(MIN_BOARD_PANE, stats, false);
+                let spare = (
+                    available.0.checked_sub(smallest.size.0)?,
+                    available.1.checked_sub(smallest.size.1)?,
+                );
+                let pane = (
+                    board_pane.0.min(MIN_BOARD_PANE.0 + spare.0),
+                    board_pane.1.min(MIN_BOARD_PANE.1 + spare.1),
+                );
+                Some(arrange(pane, stats, pane != board_pane))
+            })
+    }
+
+    /// The smallest screen any layout fits in
+    pub fn min_size() -> (usize, usize) {
+        [
+            Self::side_by_side(MIN_BOARD_PANE, false, false),
+            Self::stacked(MIN_BOARD_PANE, false, false),
+        ]
+        .iter()
+        .map(|l| l.size)
+        .min_by_key(|&(width, height)| width * height)
+        .expect("There are layouts")
+    }
+
+    /// The panels to the right of the board, with the bag along the bottom
+    pub fn side_by_side(board_pane: (usize, usize), stats: bool, map: bool) -> Layout {
+        let height = board_pane.1.max(PANELS_HEIGHT);
+        let board = Rect::new(1, 1, board_pane.0, height);
+        Self::with_panels(board, (board.right() + 1, 1), height, stats, map)
+    }
+
+    /// The panels under the board, with the bag under them
+    pub fn stacked(board_pane: (usize, usize), stats: bool, map: bool) -> Layout {
+        let board = Rect::new(1, 1, board_pane.0, board_pane.1);
+        let mut layout =
+            Self::with_panels(board, (1, board.bottom() + 1), PANELS_HEIGHT, stats, map);
+        // the board stretches across the top if the panels are wider
+        layout.board.width = layout.size.0 - 2;
+        layout
+    }
+
+    /// Puts the shop and menu column, the inspector and the stats in a row from `top_left`,
+    /// `height` rows tall, and the bag under everything. The minimap goes under the inspector if it fits
+    fn with_panels(
+        board: Rect,
+        top_left: (usize, usize),
+        height: usize,
+        stats: bool,
+        map: bool,
+    ) -> Layout {
+        let (x, y) = top_left;
+        let shop_title = Rect::new(x, y, SIDE_WIDTH, 1);
+        let shop = Rect::new(x, y + 2, SIDE_WIDTH, SHOP_HEIGHT);
+        let menu_title = Rect::new(x, shop.bottom() + 1, SIDE_WIDTH, 1);
+        // the menu takes up whatever is left of the column
+        let menu = Rect::new(
+            x,
+            menu_title.bottom() + 1,
+            SIDE_WIDTH,
+            y + height - menu_title.bottom() - 1,
+        );
+
+        let inspector_title = Rect::new(shop.right() + 1, y, INSPECTOR_WIDTH, 1);
+        let mut inspector = Rect::new(inspector_title.x, y + 2, INSPECTOR_WIDTH, height - 2);
+        let map_height = (height - 2).saturating_sub(INSPECTOR_HEIGHT + 3);
+        let map = (map && map_height >= MIN_MAP_HEIGHT).then(|| {
+            inspector.height = INSPECTOR_HEIGHT;
+            (
+                Rect::new(inspector.x, inspector.bottom() + 1, INSPECTOR_WIDTH, 1),
+                Rect::new(
+                    inspector.x,
+                    inspector.bottom() + 3,
+                    INSPECTOR_WIDTH,
+                    map_height,
+                ),
+            )
+        });
+        let stats = stats.then(|| {
+            (
+                Rect::new(inspector.right() + 1, y, STATS_WIDTH, 1),
+                Rect::new(inspector.right() + 1, y + 2, STATS_WIDTH, height - 2),
+            )
+        });
+
+        let right = stats
+            .map_or(inspector.right(), |(s, _)| s.right())
+            .max(board.right());
+        let bag_y = menu.bottom().max(board.bottom()) + 1;
+        let bag_title = Rect::new(1, bag_y, BAG_TITLE_WIDTH, BAG_HEIGHT);
+        let bag = Rect::new(
+            bag_title.right() + 1,
+            bag_y,
+            right - bag_title.right() - 1,
+            BAG_HEIGHT,
+        );
+        Layout {
+            size: (right + 1, bag.bottom() + 1),
+            board,
+            shop_title,
+            shop,
+            menu_title,
+            menu,
+            inspector_title,
+            inspector,
+            stats,
+            map,
+            bag_title,
+            bag,
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn board(&self) -> Rect {
+        self.board
+    }
+
+    pub fn shop_title(&self) -> Rect {
+        self.shop_title
+    }
+
+    pub fn shop(&self) -> Rect {
+        self.shop
+    }
+
+    pub fn menu_title(&self) -> Rect {
+        self.menu_title
+    }
+
+    pub fn menu(&self) -> Rect {
+        self.menu
+    }
+
+    pub fn inspector(&self) -> Rect {
+        self.inspector
+    }
+
+    /// None if the stats were left out
+    pub fn stats(&self) -> Option<Rect> {
+        self.stats.map(|(_, s)| s)
+    }
+
+    /// None if the whole board fits or there's no room for it
+    pub fn map(&self) -> Option<Rect> {
+        self.map.map(|(_, m)| m)
+    }
+
+    pub fn bag_title(&self) -> Rect {
+        self.bag_title
+    }
+
+    pub fn bag(&self) -> Rect {
+        self.bag
+    }
+
+    /// Every box with a border drawn around it, and the title written in it
+    pub fn boxes(&self) -> Vec<(Rect, Option<&'static str>)> {
+        let mut boxes = vec![
+            (self.board, None),
+            (self.shop_title, Some("SHOP!")),
+            (self.shop, None),
+            (self.menu_title, Some("MENU!")),
+            (self.menu, None),
+            (self.inspector_title, Some("FLOWER!")),
+            (self.inspector, None),
+            (self.bag_title, Some("BAG!")),
+            (self.bag, None),
+        ];
+        if let Some((title, stats)) = self.stats {
+            boxes.extend([(title, Some("STATS!")), (stats, None)]);
+        }
+        if let Some((title, map)) = self.map {
+            boxes.extend([(title, Some("MAP!")), (map, None)]);
+        }
+        boxes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_smallest_layout_is_side_by_side() {
+        assert_eq!(Layout::min_size(), (50, 14));
+        assert_eq!(
+            Layout::side_by_side(MIN_BOARD_PANE, false, false).size(),
+            (50, 14)
+        );
+        assert_eq!(
+            Layout::stacked(MIN_BOARD_PANE, false, false).size(),
+            (30, 24)
+        );
+    }
+
+    #[test]
+    fn panels_move_around_as_the_screen_changes() {
+        let pane = Layout::board_pane((9, 9));
+
+        // wide enough for everything next to the board
+        let wide = Layout::fit((80, 23), pane, true).unwrap();
+        assert_eq!(wide.board(), Rect::new(1, 1, 19, 9));
+        assert_eq!(wide.shop().pos(), (21, 3));
+        assert_eq!(wide.stats(), Some(Rect::new(50, 3, 20, 7)));
+        assert_eq!(wide.map(), None);
+
+        // too narrow, so under the board, which stretches across the top
+        let tall = Layout::fit((60, 40), pane, true).unwrap();
+        assert_eq!(tall.board(), Rect::new(1, 1, 49, 9));
+        assert_eq!(tall.shop().pos(), (1, 13));
+        assert!(tall.stats().is_some());
+        assert_eq!(tall.size(), (51, 24));
+
+        // neither fits with the stats, so they're left out before anything else
+        let small = Layout::fit((55, 16), pane, true).unwrap();
+        assert_eq!(small, Layout::side_by_side(pane, false, false));
+        assert_eq!(small.stats(), None);
+    }
+
+    #[test]
+    fn big_boards_shrink_to_the_screen_and_get_a_map() {
+        let pane = Layout::board_pane((64, 64));
+        assert_eq!(pane, (129, 64));
+        let layout = Layout::fit((80, 23), pane, true).unwrap();
+        assert!(layout.size().0 <= 80 && layout.size().1 <= 23);
+        assert_eq!(layout.board().size(), (28, 18));
+        assert!(layout.map().is_some());
+        // the whole board fits, so there's no map
+        let layout = Layout::fit((400, 100), pane, true).unwrap();
+        assert_eq!(layout.board().size(), pane);
+        assert_eq!(layout.map(), None);
+    }
+
+    #[test]
+    fn nothing_fits_a_tiny_screen() {
+        let pane = Layout::board_pane((9, 9));
+        assert!(Layout::fit(Layout::min_size(), pane, true).is_some());
+        assert!(Layout::fit((49, 14), pane, true).is_none());
+        assert!(Layout::fit((50, 13), pane, true).is_none());
+        // tall and narrow is too narrow even for the stacked layout
+        assert!(Layout::fit((29, 100), pane, false).is_none());
+        assert!(Layout::fit((30, 100), pane, false).is_some());
+    }
+}
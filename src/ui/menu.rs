@@ -0,0 +1,339 @@
+use crate::flowers::flower::FlowerContext;
+
+use super::{
+    Grid,
+    terminal::{AnsiColor, AnsiEffect, TextBlueprint},
+    text_row,
+};
+
+/// The panes that can have the keyboard's focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Board,
+    Shop,
+    Inventory,
+    Menu,
+}
+
+impl Pane {
+    /// The pane Tab moves the focus to
+    pub fn next(&self) -> Pane {
+        match self {
+            Pane::Board => Pane::Shop,
+            Pane::Shop => Pane::Inventory,
+            Pane::Inventory => Pane::Menu,
+            Pane::Menu => Pane::Board,
+        }
+    }
+
+    pub fn previous(&self) -> Pane {
+        match self {
+            Pane::Board => Pane::Menu,
+            Pane::Shop => Pane::Board,
+            Pane::Inventory => Pane::Shop,
+            Pane::Menu => Pane::Inventory,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    NewGame,
+    Save,
+    Load,
+    Settings,
+    Help,
+    Encyclopedia,
+    Quit,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 7] = [
+        MenuItem::NewGame,
+        MenuItem::Save,
+        MenuItem::Load,
+        MenuItem::Settings,
+        MenuItem::Help,
+        MenuItem::Encyclopedia,
+        MenuItem::Quit,
+    ];
+
+    /// Short enough to fit in the MENU! box
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuItem::NewGame => "New game",
+            MenuItem::Save => "Save",
+            MenuItem::Load => "Load",
+            MenuItem::Settings => "Settings",
+            MenuItem::Help => "Help",
+            MenuItem::Encyclopedia => "Flowerdex",
+            MenuItem::Quit => "Quit",
+        }
+    }
+
+    /// Whether picking it throws away the game being played, so it needs a yes first
+    pub fn needs_confirming(&self) -> bool {
+        matches!(self, MenuItem::NewGame | MenuItem::Load)
+    }
+}
+
+/// The menu items that open a page over the board instead of doing something right away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuPage {
+    Settings,
+    Help,
+    Encyclopedia,
+}
+
+pub struct Menu {
+    cursor: usize,
+    page: Option<MenuPage>,
+    /// The selected row of a settings page, or the shown species of the encyclopedia
+    page_cursor: usize,
+    /// The item waiting for a yes
+    confirming: Option<MenuItem>,
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Menu {
+    pub fn selected_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_background_color(AnsiColor::from_code(236))
+            .to_owned()
+    }
+
+    pub fn focused_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_text_color(AnsiColor::from_code(221))
+            .with_effect(AnsiEffect::Bold)
+            .to_owned()
+    }
+
+    pub fn help_lines() -> Vec<&'static str> {
+        vec![
+            "tab    switch pane",
+            "wasd/← move cursor",
+            "WASD   move a page",
+            "pgup   page up",
+            "pgdn   page down",
+            "home   row start",
+            "end    row end",
+            "space  plant / buy",
+            "c / f  clear/carry",
+            "g / $  pick / sell",
+            "t      family tree",
+            "e / q  water/area",
+            "n      next day",
+            "p / o  save / load",
+            "esc    quit",
+        ]
+    }
+
+    pub fn new() -> Menu {
+        Menu {
+            cursor: 0,
+            page: None,
+            page_cursor: 0,
+            confirming: None,
+        }
+    }
+
+    pub fn selected(&self) -> MenuItem {
+        MenuItem::ALL[self.cursor]
+    }
+
+    pub fn page(&self) -> Option<MenuPage> {
+        self.page
+    }
+
+    pub fn page_cursor(&self) -> usize {
+        self.page_cursor
+    }
+
+    pub fn confirming(&self) -> Option<MenuItem> {
+        self.confirming
+    }
+
+    /// Asks for a yes before the item is done
+    pub fn ask(&mut self, item: MenuItem) {
+        self.confirming = Some(item);
+    }
+
+    /// Stops waiting for an answer, giving back the item that was asked about
+    pub fn answer(&mut self) -> Option<MenuItem> {
+        self.confirming.take()
+    }
+
+    pub fn open_page(&mut self, page: MenuPage) {
+        self.page = Some(page);
+        self.page_cursor = 0;
+    }
+
+    pub fn close_page(&mut self) {
+        self.page = None;
+    }
+
+    /// Moves through the items, or through the open page. `page_length` is how many rows or species the page has
+    pub fn move_cursor(&mut self, d: isize, page_length: usize) {
+        match self.page {
+            Some(_) => {
+                self.page_cursor =
+                    (self.page_cursor as isize + d).rem_euclid(page_length.max(1) as isize) as usize
+            }
+            None => {
+                self.cursor =
+                    (self.cursor as isize + d).rem_euclid(MenuItem::ALL.len() as isize) as usize
+            }
+        }
+    }
+
+    /// The items inside the MENU! box, scrolled so the selected one is always shown,
+    /// or the question while an item is waiting for a yes
+    pub fn to_grid(&self, focused: bool, size: (usize, usize)) -> Grid<String> {
+        let (width, height) = size;
+        let mut grid: Grid<String> = match self.confirming {
+            Some(item) => vec![
+                text_row(
+                    &format!("{}?", item.label()),
+                    width,
+                    Some(&Self::focused_blueprint()),
+                ),
+                text_row("y / n", width, None),
+            ],
+            None => {
+                let first = self.cursor.saturating_sub(height.saturating_sub(1));
+                MenuItem::ALL
+                    .iter()
+                    .enumerate()
+                    .skip(first)
+                    .map(|(i, item)| {
+                        let text = format!(" {}", item.label());
+                        if i != self.cursor {
+                            text_row(&text, width, None)
+                        } else if focused {
+                            let blueprint = Self::focused_blueprint()
+                                .with_background_color(AnsiColor::from_code(236))
+                                .to_owned();
+                            text_row(&text, width, Some(&blueprint))
+                        } else {
+                            text_row(&text, width, Some(&Self::selected_blueprint()))
+                        }
+                    })
+                    .collect()
+            }
+        };
+        grid.truncate(height);
+        grid.resize(height, text_row("", width, None));
+        grid
+    }
+
+    /// The open page, drawn over the board. `settings` are the names and values of every setting
+    pub fn page_grid(
+        &self,
+        flower_context: &FlowerContext,
+        settings: &[(String, String)],
+        size: (usize, usize),
+    ) -> Option<Grid<String>> {
+        let (width, height) = size;
+        let mut grid: Grid<String> = match self.page? {
+            // scrolled by the page cursor
+            MenuPage::Help => Self::help_lines()
+                .iter()
+                .skip(self.page_cursor)
+                .map(|line| text_row(line, width, None))
+                .collect(),
+            MenuPage::Settings => settings
+                .iter()
+                .enumerate()
+                .map(|(i, (name, value))| {
+                    let text = format!(
+                        "{:<w$}{}",
+                        name,
+                        value,
+                        w = width.saturating_sub(value.len())
+                    );
+                    if i == self.page_cursor {
+                        text_row(&text, width, Some(&Self::selected_blueprint()))
+                    } else {
+                        text_row(&text, width, None)
+                    }
+                })
+                .collect(),
+            MenuPage::Encyclopedia => {
+                let flower_types = flower_context.flower_types();
+                let flower_type = *flower_types.get(self.page_cursor)?;
+                let seed = flower_context.seeds_of(flower_type).first()?.clone();
+                let mut grid = vec![];
+
+                let mut name_row = vec![seed.to_string(flower_context), " ".to_string()];
+                name_row.extend(text_row(&seed.info().name(), width.saturating_sub(2), None));
+                grid.push(name_row);
+
+                for color in flower_context.colors_of(flower_type) {
+                    // find a flower of the color to draw it with
+                    let genotype =
+                        flower_context
+                            .all_genotypes(flower_type)
+                            .into_iter()
+                            .find(|g| {
+                                flower_context.phenotype_name(flower_type, g).as_ref()
+                                    == Some(&color)
+                            });
+                    let blueprint = genotype
+                        .and_then(|g| flower_context.new_flower(flower_type, g))
+                        .map(|f| f.phenotype(flower_context).blueprint());
+                    let mut row = vec![" ".to_string()];
+                    row.extend(text_row(
+                        &format!("■ {}", color),
+                        width - 1,
+                        blueprint.as_ref(),
+                    ));
+                    grid.push(row);
+                }
+                grid
+            }
+        };
+
+        grid.resize(height, text_row("", width, None));
+        Some(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_selected_item_is_always_shown() {
+        let mut menu = Menu::new();
+        assert_eq!(menu.to_grid(false, (9, 10)).len(), 10);
+        for _ in 0..MenuItem::ALL.len() - 1 {
+            menu.move_cursor(1, 0);
+            let grid = menu.to_grid(true, (9, 3));
+            assert_eq!(grid.len(), 3);
+            // the selected row is the one drawn with a background
+            let selected = grid.iter().find(|row| row[1].contains("48;5;236")).unwrap();
+            let first_letter = &menu.selected().label()[..1];
+            assert!(selected[1].contains(first_letter));
+        }
+        assert_eq!(menu.selected(), MenuItem::Quit);
+    }
+
+    #[test]
+    fn new_game_and_load_wait_for_a_yes() {
+        let mut menu = Menu::new();
+        assert!(MenuItem::NewGame.needs_confirming());
+        assert!(MenuItem::Load.needs_confirming());
+        assert!(!MenuItem::Save.needs_confirming());
+        menu.ask(MenuItem::Load);
+        assert_eq!(menu.confirming(), Some(MenuItem::Load));
+        assert_eq!(menu.to_grid(true, (9, 1)).len(), 1);
+        assert_eq!(menu.answer(), Some(MenuItem::Load));
+        assert_eq!(menu.confirming(), None);
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(unix)]
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Switches to the alternate screen, clears it and hides the cursor
+const ENTER: &str = "\x1B[?1049h\x1B[2J\x1B[?25l";
+/// Undoes `ENTER`, and any style left over from a frame cut off halfway
+const LEAVE: &str = "\x1B[0m\x1B[?25h\x1B[?1049l";
+
+/// Whether the terminal is set up for the game and needs putting back
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// The terminal settings from before the game started
+#[cfg(unix)]
+static ORIGINAL_TERMIOS: OnceLock<libc::termios> = OnceLock::new();
+
+/// Sets the terminal up for the game while it's alive, and puts it back the way it was when dropped.
+/// It's also put back if the game panics or gets killed, so the shell is never left garbled
+pub struct TerminalSession {
+    _private: (),
+}
+
+impl TerminalSession {
+    /// Enters the alternate screen, hides the cursor and turns on raw mode.
+    /// Errors if stdin isn't a terminal
+    pub fn start() -> std::io::Result<TerminalSession> {
+        #[cfg(unix)]
+        Self::enter_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            // put the terminal back first or the panic message gets lost on the alternate screen
+            restore();
+            previous_hook(info);
+        }));
+        #[cfg(unix)]
+        for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT] {
+            // SAFETY: the handler only makes async-signal-safe calls
+            unsafe {
+                libc::signal(
+                    signal,
+                    on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t,
+                );
+            }
+        }
+
+        write_raw(ENTER);
+        Ok(TerminalSession { _private: () })
+    }
+
+    /// Keys come in as soon as they're pressed without being echoed, and Ctrl-C comes in as a key instead of a signal
+    #[cfg(unix)]
+    fn enter_raw_mode() -> std::io::Result<()> {
+        // SAFETY: termios is plain old data, and tcgetattr fills it in or fails
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        ORIGINAL_TERMIOS.get_or_init(|| termios);
+
+        termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        termios.c_cc[libc::VMIN] = 1;
+        termios.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &termios) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Puts the terminal back the way it was before the session started. Does nothing if it already is
+pub fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    write_raw(LEAVE);
+    #[cfg(unix)]
+    if let Some(termios) = ORIGINAL_TERMIOS.get() {
+        // SAFETY: the termios came from tcgetattr
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, termios);
+        }
+    }
+}
+
+/// Writes past stdout's lock and buffer, since the game might be holding them when this is needed
+fn write_raw(text: &str) {
+    #[cfg(unix)]
+    // SAFETY: the pointer and length come from a valid str
+    unsafe {
+        libc::write(libc::STDOUT_FILENO, text.as_ptr().cast(), text.len());
+    }
+    #[cfg(not(unix))]
+    {
+        use std::io::Write;
+        let mut stdout = std::io::stdout();
+        // there's nowhere to report it while the terminal is being put back
+        let _ = stdout.write_all(text.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+/// Restores the terminal and then dies of the signal like the game wasn't catching it
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    restore();
+    // SAFETY: signal and raise are async-signal-safe
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
@@ -1,10 +1,14 @@
 use crate::flowers::flower::{AnyFlower, FlowerContext};
 
 use super::{
-    Grid, empty_string_grid,
+    Grid,
     terminal::{AnsiColor, TextBlueprint},
+    text_row,
 };
 
+/// How many bells a new game starts with
+pub const STARTING_BELLS: u32 = 1000;
+
 pub struct Shop {
     shop_grid: Grid<AnyFlower>,
     shop_size: (usize, usize),
@@ -18,22 +22,43 @@ impl Shop {
             .to_owned()
     }
 
+    pub fn price_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_text_color(AnsiColor::from_code(221))
+            .to_owned()
+    }
+
     pub fn new(flower_context: &FlowerContext) -> Shop {
-        let shop_grid: Grid<AnyFlower> = flower_context.get_seeds()
-            .iter()
-            .rev()
-            .cloned()
-            .collect();
+        let shop_grid: Grid<AnyFlower> = flower_context.get_seeds().iter().rev().cloned().collect();
 
-        let shop_size = (shop_grid[0].len(), shop_grid.len());
+        let shop_size = (
+            shop_grid.iter().map(|v| v.len()).max().unwrap_or(0),
+            shop_grid.len(),
+        );
+
+        // start on the first seeds a new game can afford
+        let cursor_pos = shop_grid
+            .iter()
+            .enumerate()
+            .find_map(|(y, row)| {
+                let x = row.iter().position(|f| {
+                    flower_context.seed_price(f.info().flower_type()) <= STARTING_BELLS
+                })?;
+                Some((x, y))
+            })
+            .unwrap_or((0, 0));
 
         Shop {
             shop_grid,
             shop_size,
-            cursor_pos: (0, 0),
+            cursor_pos,
         }
     }
 
+    pub fn cursor_pos(&self) -> (usize, usize) {
+        self.cursor_pos
+    }
+
     pub fn set_cursor(&mut self, new_pos: (usize, usize)) {
         self.cursor_pos = new_pos;
     }
@@ -43,11 +68,16 @@ impl Shop {
             (self.cursor_pos.0 as isize + d_pos.0).clamp(0, self.shop_size.0 as isize - 1) as usize;
         let new_y =
             (self.cursor_pos.1 as isize - d_pos.1).clamp(0, self.shop_size.1 as isize - 1) as usize;
-        self.cursor_pos = (new_x, new_y);
+        // not every flower has as many seeds
+        let row_length = self.shop_grid[new_y].len();
+        self.cursor_pos = (new_x.min(row_length.saturating_sub(1)), new_y);
     }
 
     pub fn to_grid(&self, flower_context: &FlowerContext) -> Grid<String> {
-        let first_row_index = self.cursor_pos.1.clamp(0, self.shop_grid.len().checked_sub(2).unwrap_or_else(|| 0));
+        let first_row_index = self
+            .cursor_pos
+            .1
+            .clamp(0, self.shop_grid.len().saturating_sub(2));
         let mut displayed_grid: Grid<String> = self
             .shop_grid
             .iter()
@@ -68,26 +98,45 @@ impl Shop {
             .take(2)
             .collect();
 
-        displayed_grid.insert(1, vec![" ".to_string(); 3]);
-        if displayed_grid[0].len() < 4 {
-            displayed_grid
-                .iter_mut()
-                .for_each(|v| v.push(" ".to_string()));
-        }
+        // the price of the selected seed goes between the rows
+        let price = self
+            .selected_flower()
+            .map_or(0, |f| flower_context.seed_price(f.info().flower_type()));
+        displayed_grid.insert(1, vec![]);
+        // pad shorter rows so nothing from the last frame is left behind
         displayed_grid
+            .iter_mut()
+            .for_each(|v| v.resize(v.len().max(4), " ".to_string()));
+        let mut displayed_grid: Grid<String> = displayed_grid
             .iter()
             .map(|v| {
                 v.iter()
-                    .zip(vec![" ".to_string()].iter().cycle())
+                    .zip([" ".to_string()].iter().cycle())
                     .flat_map(|(s1, s2)| [s1, s2].into_iter().cloned())
                     .collect()
             })
-            .collect()
+            .collect();
+        displayed_grid[1] = text_row(&price.to_string(), 8, Some(&Self::price_blueprint()));
+        displayed_grid
     }
 
     pub fn selected_flower(&self) -> Option<AnyFlower> {
-        self.shop_grid.get(self.cursor_pos.1)?
+        self.shop_grid
+            .get(self.cursor_pos.1)?
             .get(self.cursor_pos.0)
             .cloned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_new_game_can_afford_the_first_seeds() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let shop = Shop::new(&flower_context);
+        let seed = shop.selected_flower().unwrap();
+        assert!(flower_context.seed_price(seed.info().flower_type()) <= STARTING_BELLS);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+
+use crate::flowers::flower::{AnyFlower, FlowerContext, FlowerType};
+use crate::genetics::Genotype;
+
+use super::{
+    Grid,
+    board::Board,
+    terminal::{AnsiColor, TextBlueprint},
+    text_row,
+};
+
+/// Numbers about the whole garden, worked out fresh every frame
+pub struct GardenStats {
+    day: u32,
+    bred_today: usize,
+    /// The glyph of each species on the board and how many there are, in shop order
+    species_counts: Vec<(char, usize)>,
+    /// Each color on the board, how to draw it, and how many flowers have it. Most common first
+    color_counts: Vec<(String, TextBlueprint, usize)>,
+    distinct_genotypes: usize,
+    /// The flower with the rarest color and that color
+    rarest: Option<(AnyFlower, String)>,
+    /// How many of the colors of every species are on the board, out of how many there are
+    collected: (usize, usize),
+}
+
+impl GardenStats {
+    pub fn label_blueprint() -> TextBlueprint {
+        TextBlueprint::new()
+            .with_text_color(AnsiColor::from_gray_value(12).expect("Couldn't get label color"))
+            .to_owned()
+    }
+
+    pub fn new(board: &Board, day: u32, bred_today: usize, flower_context: &FlowerContext) -> Self {
+        let flowers: Vec<(FlowerType, Genotype, AnyFlower, String)> = board
+            .flowers()
+            .into_iter()
+            .map(|(_, f)| {
+                let flower_type = f.info().flower_type();
+                let genotype = f.genotype();
+                let color = flower_context
+                    .phenotype_name(flower_type, &genotype)
+                    .unwrap_or_default();
+                (flower_type, genotype, f, color)
+            })
+            .collect();
+
+        let species_counts = flower_context
+            .flower_types()
+            .into_iter()
+            .filter_map(|t| {
+                let of_type: Vec<&AnyFlower> = flowers
+                    .iter()
+                    .filter(|(ft, ..)| *ft == t)
+                    .map(|(_, _, f, _)| f)
+                    .collect();
+                let glyph = of_type.first()?.phenotype(flower_context).character();
+                Some((glyph, of_type.len()))
+            })
+            .collect();
+
+        let mut color_counts: Vec<(String, TextBlueprint, usize)> = vec![];
+        for (_, _, f, color) in flowers.iter() {
+            match color_counts.iter_mut().find(|(c, ..)| c == color) {
+                Some((_, _, count)) => *count += 1,
+                None => {
+                    color_counts.push((color.clone(), f.phenotype(flower_context).blueprint(), 1))
+                }
+            }
+        }
+        color_counts.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+
+        let distinct_genotypes = flowers
+            .iter()
+            .map(|(t, g, ..)| (*t, g.clone()))
+            .collect::<HashSet<(FlowerType, Genotype)>>()
+            .len();
+
+        let rarest = flowers
+            .iter()
+            .filter_map(|(t, _, f, color)| {
+                Some((flower_context.color_rarity(*t, color)?, f, color))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, f, color)| (f.clone(), color.clone()));
+
+        let collected_colors: HashSet<(FlowerType, String)> =
+            flowers.iter().map(|(t, _, _, c)| (*t, c.clone())).collect();
+        let total_colors = flower_context
+            .flower_types()
+            .into_iter()
+            .map(|t| flower_context.colors_of(t).len())
+            .sum();
+
+        Self {
+            day,
+            bred_today,
+            species_counts,
+            color_counts,
+            distinct_genotypes,
+            rarest,
+            collected: (collected_colors.len(), total_colors),
+        }
+    }
+
+    /// Lays out groups of cells one after the other, going to the next row when a group doesn't fit
+    fn wrap(groups: Vec<Vec<String>>, width: usize, rows: usize) -> Grid<String> {
+        let mut grid: Grid<String> = vec![vec![]];
+        for group in groups {
+            let row = grid.last_mut().expect("Grid always has a row");
+            if !row.is_empty() && row.len() + group.len() > width {
+                grid.push(vec![]);
+            }
+            grid.last_mut()
+                .expect("Grid always has a row")
+                .extend(group);
+        }
+        grid.resize(rows, vec![]);
+        grid.iter_mut()
+            .for_each(|row| row.resize(width, " ".to_string()));
+        grid
+    }
+
+    pub fn to_grid(&self, flower_context: &FlowerContext, size: (usize, usize)) -> Grid<String> {
+        let (width, height) = size;
+        let label = Self::label_blueprint();
+        let mut grid: Grid<String> = vec![];
+
+        let mut day_row = text_row(&format!("day {}", self.day), width, None);
+        let bred = format!("bred {}", self.bred_today);
+        let bred_start = width.saturating_sub(bred.len());
+        day_row.splice(bred_start.., text_row(&bred, width - bred_start, None));
+        grid.push(day_row);
+
+        let species_groups = self
+            .species_counts
+            .iter()
+            .map(|(glyph, count)| {
+                let text = format!("{}{} ", glyph, count);
+                text_row(&text, text.chars().count(), None)
+            })
+            .collect();
+        grid.extend(Self::wrap(species_groups, width, 1));
+
+        let color_groups = self
+            .color_counts
+            .iter()
+            .map(|(_, blueprint, count)| {
+                let mut group = vec![blueprint.apply("■")];
+                let text = format!("{} ", count);
+                group.extend(text_row(&text, text.len(), None));
+                group
+            })
+            .collect();
+        grid.extend(Self::wrap(color_groups, width, 2));
+
+        let mut genotype_row = text_row("genotypes ", 10, Some(&label));
+        genotype_row.extend(text_row(
+            &self.distinct_genotypes.to_string(),
+            width - 10,
+            None,
+        ));
+        grid.push(genotype_row);
+
+        let mut rarest_row = text_row("rarest ", 7, Some(&label));
+        match &self.rarest {
+            Some((flower, color)) => {
+                let phenotype = flower.phenotype(flower_context);
+                rarest_row.push(phenotype.to_string());
+                rarest_row.push(" ".to_string());
+                rarest_row.extend(text_row(color, width - 9, Some(&phenotype.blueprint())));
+            }
+            None => rarest_row.extend(text_row("-", width - 7, None)),
+        }
+        grid.push(rarest_row);
+
+        let mut collected_row = text_row("colors ", 7, Some(&label));
+        collected_row.extend(text_row(
+            &format!("{}/{}", self.collected.0, self.collected.1),
+            width - 7,
+            None,
+        ));
+        grid.push(collected_row);
+
+        grid.resize(height, text_row("", width, None));
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flowers::flower::ACNHFlowerType;
+    use crate::ui::backend::Frame;
+
+    #[test]
+    fn the_garden_is_counted() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let roses = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Rose));
+        let tulips = flower_context.seeds_of(FlowerType::ACNH(ACNHFlowerType::Tulip));
+        let mut board = Board::new((3, 3)).unwrap();
+        board.set_flower((0, 0), Some(roses[0].clone()));
+        board.set_flower((1, 0), Some(roses[0].clone()));
+        board.set_flower((2, 0), Some(roses[1].clone()));
+        board.set_flower((0, 1), Some(tulips[0].clone()));
+
+        let stats = GardenStats::new(&board, 3, 2, &flower_context);
+        let frame = Frame::from_grid(&stats.to_grid(&flower_context, (20, 8)), "");
+        let lines: Vec<&str> = frame.lines().iter().map(|l| l.trim_end()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "day 3         bred 2",
+                "@3 ♆1",
+                "■3 ■1",
+                "",
+                "genotypes 3",
+                "rarest @ Red",
+                "colors 3/53",
+                "",
+            ]
+        );
+        // red counts the roses and the tulip together, most common first
+        assert_eq!(frame.style_at((0, 2)), "38;5;160");
+        assert_eq!(frame.style_at((3, 2)), "38;5;7");
+        assert_eq!(frame.style_at((0, 4)), "38;5;244");
+    }
+
+    #[test]
+    fn an_empty_garden_has_nothing_to_count() {
+        let flower_context = FlowerContext::acnh_only().unwrap();
+        let board = Board::new((3, 3)).unwrap();
+        let stats = GardenStats::new(&board, 0, 0, &flower_context);
+        let frame = Frame::from_grid(&stats.to_grid(&flower_context, (20, 7)), "");
+        assert_eq!(frame.lines()[4].trim_end(), "genotypes 0");
+        assert_eq!(frame.lines()[5].trim_end(), "rarest -");
+        assert_eq!(frame.lines()[6].trim_end(), "colors 0/53");
+    }
+}
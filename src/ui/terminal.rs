@@ -1,11 +1,10 @@
-use getch_rs::{Getch, Key, disable_echo_input};
-use std::io::StdoutLock;
-use std::thread;
-use std::vec::IntoIter;
+use getch_rs::{Getch, Key};
+use std::time::Duration;
 use std::{
-    io::{Error, Write},
+    io::Error,
     str::FromStr,
-    sync::{Arc, Mutex},
+    sync::atomic::{AtomicI32, Ordering},
+    sync::mpsc::{self, Receiver, Sender},
     thread::JoinHandle,
 };
 
@@ -38,7 +37,7 @@ impl AnsiColor {
         }
     }
 
-    fn get_code(&self) -> u8 {
+    pub fn get_code(&self) -> u8 {
         self.code
     }
 }
@@ -61,6 +60,12 @@ pub struct TextBlueprint {
     effects: Vec<AnsiEffect>,
 }
 
+impl Default for TextBlueprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TextBlueprint {
     pub fn new() -> Self {
         Self {
@@ -110,7 +115,7 @@ impl TextBlueprint {
         if ansi.ends_with(';') {
             ansi.pop();
         }
-        ansi.push_str("m");
+        ansi.push('m');
         ansi
     }
 
@@ -122,68 +127,169 @@ impl TextBlueprint {
     }
 }
 
-/// The inside of the KeyStack
-#[derive(Debug, Clone)]
-pub struct KeyStackInner {
-    keys: Vec<Key>,
-    quit: bool,
+/// Something the game loop wakes up for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Key(Key),
+    /// The terminal changed to this size
+    Resize((usize, usize)),
+    /// Time passed, sent every tick of the tick rate
+    Tick,
+    /// Escape or Ctrl-C was pressed
+    Quit,
+    /// Something went wrong in one of the sources, for the status line
+    Error(String),
 }
 
-/// The thread-safe version of KeyStackInner
-pub type KeyStack = Arc<Mutex<KeyStackInner>>;
-
-impl KeyStackInner {
-    fn new() -> KeyStackInner {
-        Self {
-            keys: vec![],
-            quit: false,
+impl Event {
+    /// The event a pressed key makes. Escape and Ctrl-C quit
+    pub fn from_key(key: Key) -> Event {
+        match key {
+            Key::Esc | Key::Ctrl('c') => Event::Quit,
+            k => Event::Key(k),
         }
     }
+}
 
-    /// Returns an iterator over the keys pressed since the last poll and clears the poll
-    pub fn poll_keys(&mut self) -> IntoIter<Key> {
-        let it: IntoIter<Key> = self.keys.clone().into_iter();
-        self.keys.clear();
-        it
-    }
+/// How long the keyboard thread waits before reading again after it failed
+const KEYBOARD_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Every event from every source in the order they happened. Sources are threads that send into it,
+/// so the game loop can sleep until there's something to do
+pub struct EventStream {
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+}
 
-    /// `true` if there was an Escape Key input received, `false` otherwise
-    pub fn should_quit(&self) -> bool {
-        self.quit
+impl Default for EventStream {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-/// Generates a KeyStack with its polling thread. Used for getting inputs without them showing up on the terminal
-pub fn new_keystack() -> (KeyStack, JoinHandle<()>) {
-    // create a thread-safe keystack that can be accessed by the main thread and the new thread
-    let mutex = Arc::new(Mutex::new(KeyStackInner::new()));
-    let t_mutex = Arc::clone(&mutex);
-
-    // create a thread so we don't block the main thread while waiting for keyboard presses
-    let guard = std::thread::spawn(move || {
-        // make the get character thing
-        let g = Getch::new();
-        // keep waiting for a character input
-        loop {
-            let c = g.getch();
-            match c {
-                // if the character couldn't be received explode the computer
-                Err(e) => {
-                    println!("An error occured while getting input: {:?}", e);
-                    t_mutex.lock().unwrap().quit = true;
+impl EventStream {
+    /// A stream with no sources, for feeding events in by hand like from a script
+    pub fn new() -> EventStream {
+        let (sender, receiver) = mpsc::channel();
+        EventStream { sender, receiver }
+    }
+
+    pub fn send(&self, event: Event) {
+        self.sender
+            .send(event)
+            .expect("The stream holds its own receiver");
+    }
+
+    /// Waits for the next event
+    pub fn next(&self) -> Event {
+        self.receiver
+            .recv()
+            .expect("The stream holds its own sender")
+    }
+
+    /// The next event if one has already happened
+    pub fn try_next(&self) -> Option<Event> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Starts a thread sending every key pressed, without them showing up on the terminal.
+    /// It stops once a key quits. If reading fails it says so and tries again a bit later
+    pub fn spawn_keyboard(&self) -> JoinHandle<()> {
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            // make the get character thing
+            let g = Getch::new();
+            loop {
+                let event = match g.getch() {
+                    Err(e) => {
+                        let message = format!("Couldn't read the keyboard: {}", e);
+                        if sender.send(Event::Error(message)).is_err() {
+                            break;
+                        }
+                        std::thread::sleep(KEYBOARD_RETRY_DELAY);
+                        continue;
+                    }
+                    Ok(k) => Event::from_key(k),
+                };
+                let quit = event == Event::Quit;
+                if sender.send(event).is_err() || quit {
+                    break;
                 }
-                // if the character is escape key quit the loop
-                Ok(Key::Esc) => {
-                    t_mutex.lock().unwrap().quit = true;
+            }
+        })
+    }
+
+    /// Starts a thread sending the new size whenever the terminal is resized, until the stream is gone.
+    /// Errors if the pipe the signal handler writes to can't be made
+    #[cfg(unix)]
+    pub fn spawn_resize_watcher(&self) -> Result<JoinHandle<()>, Error> {
+        let sender = self.sender.clone();
+        let mut fds = [0; 2];
+        // SAFETY: pipe fills in the two fds, and the handler only makes async-signal-safe calls
+        unsafe {
+            if libc::pipe(fds.as_mut_ptr()) == -1 {
+                return Err(Error::last_os_error());
+            }
+            RESIZE_PIPE.store(fds[1], Ordering::SeqCst);
+            libc::signal(
+                libc::SIGWINCH,
+                on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+        Ok(std::thread::spawn(move || {
+            let mut byte = 0u8;
+            // SAFETY: reads one byte into a byte
+            while unsafe { libc::read(fds[0], (&mut byte as *mut u8).cast(), 1) } == 1 {
+                let Some(size) = terminal_size() else {
+                    continue;
+                };
+                if sender.send(Event::Resize(size)).is_err() {
+                    break;
                 }
-                // otherwise return the character inputted
-                Ok(k) => {
-                    t_mutex.lock().unwrap().keys.push(k);
+            }
+        }))
+    }
+
+    /// Starts a thread sending a tick every `tick_rate` until the stream is gone
+    pub fn spawn_ticker(&self, tick_rate: Duration) -> JoinHandle<()> {
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(tick_rate);
+                if sender.send(Event::Tick).is_err() {
+                    break;
                 }
             }
-        }
-    });
+        })
+    }
+}
+
+/// The write end of the pipe the resize signal handler wakes the watcher thread with
+#[cfg(unix)]
+static RESIZE_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+#[cfg(unix)]
+extern "C" fn on_resize(_: libc::c_int) {
+    let fd = RESIZE_PIPE.load(Ordering::SeqCst);
+    // SAFETY: write is async-signal-safe, and a failed write only means a missed resize
+    unsafe {
+        libc::write(fd, [0u8].as_ptr().cast(), 1);
+    }
+}
 
-    // return the things the program needs
-    (mutex, guard)
+/// The columns and rows of the terminal, None if the output isn't one
+pub fn terminal_size() -> Option<(usize, usize)> {
+    #[cfg(unix)]
+    {
+        // SAFETY: winsize is plain old data, and ioctl fills it in or fails
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0
+            || size.ws_col == 0
+        {
+            return None;
+        }
+        Some((size.ws_col as usize, size.ws_row as usize))
+    }
+    #[cfg(not(unix))]
+    None
 }